@@ -0,0 +1,241 @@
+use crate::clock::now_secs;
+use crate::db::Account;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory (inside the node data directory) that holds archived account data.
+pub const ARCHIVE_DIR_NAME: &str = "archived";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const DATA_DIR_NAME: &str = "data";
+
+/// Written next to the archived node data so that the archive can be
+/// identified (and restored by hand) without the database.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchiveManifest {
+    pub account: Account,
+    pub original_path: String,
+    pub archived_at: i64,
+    pub app_version: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountArchive {
+    pub id: String,
+    pub path: String,
+    pub manifest: ArchiveManifest,
+}
+
+/// Resolves an account datapath against the data directory, refusing
+/// absolute paths and parent components so nothing outside it can be touched.
+pub fn resolve_datapath(data_dir: &Path, datapath: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(datapath);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(format!("Invalid account datapath: {}", datapath));
+    }
+    Ok(data_dir.join(relative))
+}
+
+fn archive_root(data_dir: &Path) -> PathBuf {
+    data_dir.join(ARCHIVE_DIR_NAME)
+}
+
+/// Number of files and their total size under `path`.
+fn dir_summary(path: &Path) -> Result<(u64, u64), String> {
+    let mut files = 0;
+    let mut bytes = 0;
+    let entries = fs::read_dir(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let metadata = entry
+            .metadata()
+            .map_err(|e| format!("Failed to read {:?}: {}", entry.path(), e))?;
+        if metadata.is_dir() {
+            let (dir_files, dir_bytes) = dir_summary(&entry.path())?;
+            files += dir_files;
+            bytes += dir_bytes;
+        } else {
+            files += 1;
+            bytes += metadata.len();
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// Copies `source` to `target`, checks the copy and only then removes the
+/// source. A failed or incomplete copy is dropped and the source kept; if
+/// the source can't be removed afterwards both are kept.
+fn copy_then_remove(source: &Path, target: &Path) -> Result<(), String> {
+    fs::create_dir_all(target)
+        .map_err(|e| format!("Failed to create archive directory {:?}: {}", target, e))?;
+    let mut options = fs_extra::dir::CopyOptions::new();
+    options.content_only = true;
+    let copied = fs_extra::dir::copy(source, target, &options)
+        .map_err(|e| format!("Failed to copy {:?} to {:?}: {}", source, target, e))
+        .and_then(|_| {
+            let expected = dir_summary(source)?;
+            let actual = dir_summary(target)?;
+            if expected == actual {
+                Ok(())
+            } else {
+                Err(format!(
+                    "The copy of {:?} in {:?} is incomplete ({} of {} files, {} of {} bytes)",
+                    source, target, actual.0, expected.0, actual.1, expected.1
+                ))
+            }
+        });
+    if let Err(e) = copied {
+        // The source is untouched, only drop the partial copy
+        let _ = fs::remove_dir_all(target);
+        return Err(e);
+    }
+
+    fs::remove_dir_all(source).map_err(|e| {
+        format!(
+            "Copied the node data to {:?} but failed to remove {:?}: {}. Both copies were kept.",
+            target, source, e
+        )
+    })
+}
+
+fn move_dir(source: &Path, target: &Path) -> Result<(), String> {
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    // Renaming fails across filesystems
+    copy_then_remove(source, target)
+}
+
+/// Moves the account's node data into a timestamped archive directory with a
+/// manifest. Returns the archive path, or `None` if there was nothing to archive.
+pub fn archive_account_data(data_dir: &Path, account: &Account) -> Result<Option<PathBuf>, String> {
+    let datapath = match &account.datapath {
+        Some(datapath) if !datapath.is_empty() => datapath,
+        _ => return Ok(None),
+    };
+
+    let source = resolve_datapath(data_dir, datapath)?;
+    if !source.exists() {
+        println!(
+            "No node data found at {:?} for account {}, nothing to archive",
+            source, account.name
+        );
+        return Ok(None);
+    }
+
+    let archived_at = now_secs();
+    let root = archive_root(data_dir);
+    let mut target = root.join(format!("{}-{}", datapath, archived_at));
+    let mut suffix = 1;
+    while target.exists() {
+        target = root.join(format!("{}-{}-{}", datapath, archived_at, suffix));
+        suffix += 1;
+    }
+    fs::create_dir_all(&target)
+        .map_err(|e| format!("Failed to create archive directory {:?}: {}", target, e))?;
+
+    let manifest = ArchiveManifest {
+        account: account.clone(),
+        original_path: source.to_string_lossy().to_string(),
+        archived_at,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(target.join(MANIFEST_FILE_NAME), manifest_json)
+        .map_err(|e| format!("Failed to write archive manifest: {}", e))?;
+
+    println!("Archiving node data from {:?} to {:?}", source, target);
+    let data = target.join(DATA_DIR_NAME);
+    if let Err(e) = move_dir(&source, &data) {
+        // Drop the archive only if it holds no copy of the data, `move_dir`
+        // keeps a complete copy when the source could not be removed
+        if !data.exists() {
+            let _ = fs::remove_dir_all(&target);
+        }
+        return Err(e);
+    }
+
+    Ok(Some(target))
+}
+
+/// Lists every archive that has a readable manifest, newest first.
+pub fn list_archives(data_dir: &Path) -> Result<Vec<AccountArchive>, String> {
+    let root = archive_root(data_dir);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries =
+        fs::read_dir(&root).map_err(|e| format!("Failed to read archive directory: {}", e))?;
+    let mut archives = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let manifest = match fs::read_to_string(path.join(MANIFEST_FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<ArchiveManifest>(&contents).ok())
+        {
+            Some(manifest) => manifest,
+            None => continue,
+        };
+        archives.push(AccountArchive {
+            id: entry.file_name().to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            manifest,
+        });
+    }
+
+    archives.sort_by_key(|archive| std::cmp::Reverse(archive.manifest.archived_at));
+    Ok(archives)
+}
+
+/// Permanently deletes an archive. `confirmation` must match the name of the
+/// archived account, so a purge can never happen by accident.
+pub fn purge_archive(data_dir: &Path, archive_id: &str, confirmation: &str) -> Result<(), String> {
+    let root = archive_root(data_dir);
+    let target = resolve_datapath(&root, archive_id)?;
+
+    let archive = list_archives(data_dir)?
+        .into_iter()
+        .find(|archive| archive.id == archive_id)
+        .ok_or_else(|| format!("Archive not found: {}", archive_id))?;
+
+    if archive.manifest.account.name != confirmation {
+        return Err(
+            "Confirmation does not match the archived account name. Nothing was deleted."
+                .to_string(),
+        );
+    }
+
+    println!("Permanently deleting account archive at {:?}", target);
+    fs::remove_dir_all(&target).map_err(|e| format!("Failed to delete archive: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_then_remove_keeps_a_checked_copy() {
+        let dir = std::env::temp_dir().join(format!("kaleidoswap-archive-test-{}", now_secs()));
+        let source = dir.join("source");
+        fs::create_dir_all(source.join(".ldk")).unwrap();
+        fs::write(source.join("wallet.db"), b"wallet").unwrap();
+        fs::write(source.join(".ldk").join("channel_manager"), b"channels").unwrap();
+        let expected = dir_summary(&source).unwrap();
+        assert_eq!(expected, (2, 14));
+
+        let target = dir.join("archive").join(DATA_DIR_NAME);
+        copy_then_remove(&source, &target).unwrap();
+        assert!(!source.exists());
+        assert_eq!(dir_summary(&target).unwrap(), expected);
+
+        // A missing source fails without leaving a partial copy behind
+        let other = dir.join("other");
+        assert!(copy_then_remove(&source, &other).is_err());
+        assert!(!other.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
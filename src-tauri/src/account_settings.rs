@@ -0,0 +1,352 @@
+use crate::db;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Emitted to the frontend whenever a setting changes. Background tasks read
+/// settings on every tick, so they pick up the change without listening.
+pub const SETTING_CHANGED_EVENT: &str = "account-setting-changed";
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettingKind {
+    Bool,
+    Integer { min: i64, max: i64 },
+    String { max_length: usize },
+    Enum { options: &'static [&'static str] },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub kind: SettingKind,
+    pub default: Value,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountSetting {
+    pub key: String,
+    pub value: Value,
+    pub is_default: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SettingChangedEvent {
+    pub account_name: String,
+    pub key: String,
+    pub value: Value,
+}
+
+/// Every per-account setting the app knows about. Values that are not listed
+/// here are rejected, so the backend and frontend always agree on the types.
+pub fn schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition {
+            key: "bitcoin_unit",
+            description: "Unit used to display BTC amounts",
+            kind: SettingKind::Enum {
+                options: &["SAT", "BTC"],
+            },
+            default: Value::from("SAT"),
+        },
+        SettingDefinition {
+            key: "auto_unlock",
+            description: "Unlock the node automatically after it starts",
+            kind: SettingKind::Bool,
+            default: Value::from(false),
+        },
+        SettingDefinition {
+            key: "start_node_on_launch",
+            description: "Start the local node when the app launches",
+            kind: SettingKind::Bool,
+            default: Value::from(false),
+        },
+        SettingDefinition {
+            key: "auto_ports",
+            description: "Start the node on free ports when the configured ones are busy",
+            kind: SettingKind::Bool,
+            default: Value::from(false),
+        },
+        SettingDefinition {
+            key: "sync_interval_secs",
+            description: "Seconds between background wallet syncs and transfer refreshes",
+            kind: SettingKind::Integer { min: 15, max: 3600 },
+            default: Value::from(60),
+        },
+        SettingDefinition {
+            key: "backup_directory",
+            description: "Directory receiving the scheduled node backups (empty disables them)",
+            kind: SettingKind::String { max_length: 4096 },
+            default: Value::from(""),
+        },
+        SettingDefinition {
+            key: "backup_interval_hours",
+            description: "Hours between scheduled node backups (0 disables them)",
+            kind: SettingKind::Integer { min: 0, max: 720 },
+            default: Value::from(0),
+        },
+        SettingDefinition {
+            key: "backup_on_channel_change",
+            description: "Back up the node after a channel opens or closes",
+            kind: SettingKind::Bool,
+            default: Value::from(true),
+        },
+        SettingDefinition {
+            key: "backup_generations",
+            description: "Number of scheduled node backups to keep",
+            kind: SettingKind::Integer { min: 1, max: 100 },
+            default: Value::from(7),
+        },
+        SettingDefinition {
+            key: "utxo_maintenance",
+            description: "Create colored UTXOs automatically when free ones run low",
+            kind: SettingKind::Bool,
+            default: Value::from(false),
+        },
+        SettingDefinition {
+            key: "utxo_min_free",
+            description: "Create UTXOs when fewer free colored UTXOs than this are left",
+            kind: SettingKind::Integer { min: 1, max: 50 },
+            default: Value::from(3),
+        },
+        SettingDefinition {
+            key: "utxo_target_free",
+            description: "Free colored UTXOs to have after creating new ones",
+            kind: SettingKind::Integer { min: 1, max: 100 },
+            default: Value::from(10),
+        },
+        SettingDefinition {
+            key: "utxo_size_sat",
+            description: "Size in sats of automatically created UTXOs",
+            kind: SettingKind::Integer {
+                min: 1_000,
+                max: 10_000_000,
+            },
+            default: Value::from(32_500),
+        },
+        SettingDefinition {
+            key: "utxo_fee_rate",
+            description: "Fee rate in sat/vB for automatically created UTXOs",
+            kind: SettingKind::Integer { min: 1, max: 1_000 },
+            default: Value::from(5),
+        },
+        SettingDefinition {
+            key: "liquidity_alerts",
+            description: "Alert when channel liquidity drops below the thresholds",
+            kind: SettingKind::Bool,
+            default: Value::from(false),
+        },
+        SettingDefinition {
+            key: "liquidity_min_inbound_sat",
+            description: "Alert when a channel can receive fewer sats than this",
+            kind: SettingKind::Integer {
+                min: 0,
+                max: 100_000_000,
+            },
+            default: Value::from(50_000),
+        },
+        SettingDefinition {
+            key: "liquidity_min_outbound_sat",
+            description: "Alert when a channel can send fewer sats than this",
+            kind: SettingKind::Integer {
+                min: 0,
+                max: 100_000_000,
+            },
+            default: Value::from(50_000),
+        },
+        SettingDefinition {
+            key: "liquidity_min_asset_percent",
+            description: "Alert when the local or remote share of a channel's RGB asset drops below this percentage",
+            kind: SettingKind::Integer { min: 0, max: 50 },
+            default: Value::from(10),
+        },
+        SettingDefinition {
+            key: "lsp_auto_order",
+            description: "Order an inbound channel from the default LSP when total inbound liquidity is below the minimum",
+            kind: SettingKind::Bool,
+            default: Value::from(false),
+        },
+        SettingDefinition {
+            key: "lsp_order_inbound_sat",
+            description: "Inbound capacity in sats of automatically ordered channels",
+            kind: SettingKind::Integer {
+                min: 10_000,
+                max: 100_000_000,
+            },
+            default: Value::from(1_000_000),
+        },
+        SettingDefinition {
+            key: "lsp_order_max_fee_sat",
+            description: "Pay automatic LSP orders costing at most this many sats (0 leaves payment to you)",
+            kind: SettingKind::Integer {
+                min: 0,
+                max: 1_000_000,
+            },
+            default: Value::from(0),
+        },
+    ]
+}
+
+fn definition(key: &str) -> Result<SettingDefinition, String> {
+    schema()
+        .into_iter()
+        .find(|definition| definition.key == key)
+        .ok_or_else(|| format!("Unknown setting: {}", key))
+}
+
+/// Checks that `value` matches the type and bounds declared for the setting.
+pub fn validate(definition: &SettingDefinition, value: &Value) -> Result<(), String> {
+    let valid = match &definition.kind {
+        SettingKind::Bool => value.is_boolean(),
+        SettingKind::Integer { min, max } => value
+            .as_i64()
+            .is_some_and(|number| number >= *min && number <= *max),
+        SettingKind::String { max_length } => value
+            .as_str()
+            .is_some_and(|text| text.chars().count() <= *max_length),
+        SettingKind::Enum { options } => value
+            .as_str()
+            .is_some_and(|option| options.contains(&option)),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid value {} for setting '{}' (expected {:?})",
+            value, definition.key, definition.kind
+        ))
+    }
+}
+
+/// Returns the value of a setting, falling back to its default when unset
+/// or when the stored value no longer matches the schema.
+pub fn get(account_name: &str, key: &str) -> Result<Value, String> {
+    let definition = definition(key)?;
+    let stored = db::get()
+        .get_account_setting_value(account_name, key)
+        .map_err(|e| e.to_string())?;
+
+    Ok(stored
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .filter(|value| validate(&definition, value).is_ok())
+        .unwrap_or(definition.default))
+}
+
+pub fn get_bool(account_name: &str, key: &str) -> Result<bool, String> {
+    get(account_name, key)?
+        .as_bool()
+        .ok_or_else(|| format!("Setting '{}' is not a boolean", key))
+}
+
+pub fn get_i64(account_name: &str, key: &str) -> Result<i64, String> {
+    get(account_name, key)?
+        .as_i64()
+        .ok_or_else(|| format!("Setting '{}' is not an integer", key))
+}
+
+pub fn get_string(account_name: &str, key: &str) -> Result<String, String> {
+    get(account_name, key)?
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("Setting '{}' is not a string", key))
+}
+
+/// Lists every known setting for the account, with defaults filled in.
+pub fn list(account_name: &str) -> Result<Vec<AccountSetting>, String> {
+    let stored = db::get()
+        .get_account_setting_values(account_name)
+        .map_err(|e| e.to_string())?;
+
+    Ok(schema()
+        .into_iter()
+        .map(|definition| {
+            let value = stored
+                .iter()
+                .find(|(key, _)| key == definition.key)
+                .and_then(|(_, raw)| serde_json::from_str::<Value>(raw).ok())
+                .filter(|value| validate(&definition, value).is_ok());
+            AccountSetting {
+                key: definition.key.to_string(),
+                is_default: value.is_none(),
+                value: value.unwrap_or(definition.default),
+            }
+        })
+        .collect())
+}
+
+/// Validates and stores a setting. A `null` value resets it to its default.
+/// Returns the event to broadcast.
+pub fn set(account_name: &str, key: &str, value: Value) -> Result<SettingChangedEvent, String> {
+    let definition = definition(key)?;
+
+    let value = if value.is_null() {
+        db::get()
+            .delete_account_setting_value(account_name, key)
+            .map_err(|e| e.to_string())?;
+        definition.default
+    } else {
+        validate(&definition, &value)?;
+        db::get()
+            .set_account_setting_value(account_name, key, &value.to_string())
+            .map_err(|e| e.to_string())?;
+        value
+    };
+
+    Ok(SettingChangedEvent {
+        account_name: account_name.to_string(),
+        key: key.to_string(),
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_wrong_types_ranges_and_options() {
+        let auto_unlock = definition("auto_unlock").unwrap();
+        assert!(validate(&auto_unlock, &Value::from(true)).is_ok());
+        assert!(validate(&auto_unlock, &Value::from("true")).is_err());
+        assert!(validate(&auto_unlock, &Value::from(1)).is_err());
+
+        let integer = SettingDefinition {
+            key: "interval",
+            description: "",
+            kind: SettingKind::Integer { min: 15, max: 3600 },
+            default: Value::from(60),
+        };
+        assert!(validate(&integer, &Value::from(15)).is_ok());
+        assert!(validate(&integer, &Value::from(3600)).is_ok());
+        assert!(validate(&integer, &Value::from(14)).is_err());
+        assert!(validate(&integer, &Value::from(3601)).is_err());
+        assert!(validate(&integer, &Value::from(60.5)).is_err());
+        assert!(validate(&integer, &Value::from("60")).is_err());
+
+        let unit = definition("bitcoin_unit").unwrap();
+        assert!(validate(&unit, &Value::from("BTC")).is_ok());
+        assert!(validate(&unit, &Value::from("btc")).is_err());
+        assert!(validate(&unit, &Value::from("EUR")).is_err());
+
+        let text = SettingDefinition {
+            key: "note",
+            description: "",
+            kind: SettingKind::String { max_length: 3 },
+            default: Value::from(""),
+        };
+        assert!(validate(&text, &Value::from("abc")).is_ok());
+        assert!(validate(&text, &Value::from("abcd")).is_err());
+    }
+
+    #[test]
+    fn every_default_is_valid() {
+        for definition in schema() {
+            assert!(
+                validate(&definition, &definition.default).is_ok(),
+                "{}",
+                definition.key
+            );
+        }
+    }
+}
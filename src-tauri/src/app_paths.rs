@@ -0,0 +1,278 @@
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const APP_IDENTIFIER: &str = "com.kaleidoswap.dev";
+
+/// Environment variable overriding the data root.
+pub const DATA_DIR_ENV: &str = "KALEIDOSWAP_DATA_DIR";
+/// CLI flag overriding the data root, as `--data-dir <path>` or `--data-dir=<path>`.
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+/// CLI flag enabling portable mode.
+pub const PORTABLE_FLAG: &str = "--portable";
+/// A file with this name next to the executable also enables portable mode.
+pub const PORTABLE_MARKER: &str = "portable";
+/// Directory next to the executable holding everything in portable mode.
+const PORTABLE_DIR: &str = "KaleidoSwapData";
+
+static APP_PATHS: OnceLock<AppPaths> = OnceLock::new();
+
+/// Where the data root came from, in order of precedence.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RootSource {
+    CliFlag,
+    EnvVar,
+    Portable,
+    Development,
+    Platform,
+}
+
+/// Every directory the app writes to, resolved once at startup.
+#[derive(Debug, Serialize, Clone)]
+pub struct AppPaths {
+    pub source: RootSource,
+    pub root: PathBuf,
+    /// Holds `database.sqlite` and its rolling backups.
+    pub db_dir: PathBuf,
+    /// Holds the node data directory of each local account.
+    pub data_dir: PathBuf,
+    pub log_dir: PathBuf,
+}
+
+impl AppPaths {
+    fn from_root(root: PathBuf, source: RootSource) -> Self {
+        // Development builds keep node data in the repo's bin directory,
+        // next to the rgb-lightning-node binary
+        let data_dir = if source == RootSource::Development {
+            root.join("../bin")
+        } else {
+            root.join("data")
+        };
+        AppPaths {
+            source,
+            db_dir: root.join("db"),
+            log_dir: root.join("logs"),
+            data_dir,
+            root,
+        }
+    }
+
+    /// Resolves the paths from CLI arguments and the environment.
+    pub fn resolve<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let exe_dir = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        Self::resolve_from(
+            args,
+            env::var(DATA_DIR_ENV).ok(),
+            exe_dir,
+            cfg!(debug_assertions),
+        )
+    }
+
+    /// `resolve` with the environment passed in: the `DATA_DIR_ENV` value,
+    /// the executable's directory and whether this is a development build.
+    fn resolve_from<I: IntoIterator<Item = String>>(
+        args: I,
+        env_root: Option<String>,
+        exe_dir: Option<PathBuf>,
+        development: bool,
+    ) -> Result<Self, String> {
+        let args: Vec<String> = args.into_iter().collect();
+
+        if let Some(root) = data_dir_arg(&args)? {
+            return Ok(Self::from_root(absolute(root)?, RootSource::CliFlag));
+        }
+
+        if let Some(root) = env_root.filter(|root| !root.trim().is_empty()) {
+            return Ok(Self::from_root(
+                absolute(PathBuf::from(root))?,
+                RootSource::EnvVar,
+            ));
+        }
+
+        if let Some(exe_dir) = exe_dir {
+            if args.iter().any(|arg| arg == PORTABLE_FLAG) || exe_dir.join(PORTABLE_MARKER).exists()
+            {
+                return Ok(Self::from_root(
+                    exe_dir.join(PORTABLE_DIR),
+                    RootSource::Portable,
+                ));
+            }
+        }
+
+        if development {
+            return Ok(Self::from_root(
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+                RootSource::Development,
+            ));
+        }
+
+        Ok(Self::from_root(platform_root()?, RootSource::Platform))
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.db_dir.join("database.sqlite")
+    }
+
+    pub fn node_log_path(&self) -> PathBuf {
+        self.log_dir.join("rgb-lightning-node.log")
+    }
+
+    /// Creates every directory the app writes to.
+    pub fn ensure_dirs(&self) -> Result<(), String> {
+        for dir in [&self.root, &self.db_dir, &self.data_dir, &self.log_dir] {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", dir, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn data_dir_arg(args: &[String]) -> Result<Option<PathBuf>, String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == DATA_DIR_FLAG {
+            return match iter.next() {
+                Some(value) => Ok(Some(PathBuf::from(value))),
+                None => Err(format!("{} requires a path", DATA_DIR_FLAG)),
+            };
+        }
+        if let Some(value) = arg.strip_prefix(&format!("{}=", DATA_DIR_FLAG)) {
+            return Ok(Some(PathBuf::from(value)));
+        }
+    }
+    Ok(None)
+}
+
+fn absolute(path: PathBuf) -> Result<PathBuf, String> {
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    env::current_dir()
+        .map(|dir| dir.join(path))
+        .map_err(|e| format!("Failed to resolve the data directory: {}", e))
+}
+
+fn platform_root() -> Result<PathBuf, String> {
+    if cfg!(target_os = "macos") {
+        let home = env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
+        Ok(PathBuf::from(home)
+            .join("Library/Application Support")
+            .join(APP_IDENTIFIER))
+    } else if cfg!(target_os = "windows") {
+        let local_app_data =
+            env::var("LOCALAPPDATA").map_err(|e| format!("Failed to get LOCALAPPDATA: {}", e))?;
+        Ok(PathBuf::from(local_app_data).join(APP_IDENTIFIER))
+    } else {
+        let home = env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
+        Ok(PathBuf::from(home)
+            .join(".local/share")
+            .join(APP_IDENTIFIER))
+    }
+}
+
+/// Resolves the paths from the process arguments and creates the directories.
+/// Must run before any subsystem touches the disk.
+pub fn init() -> Result<&'static AppPaths, String> {
+    let paths = AppPaths::resolve(env::args().skip(1))?;
+    paths.ensure_dirs()?;
+    println!("Using data root {:?} ({:?})", paths.root, paths.source);
+    Ok(APP_PATHS.get_or_init(|| paths))
+}
+
+/// Returns the resolved paths, resolving them on first use if `init` was not called.
+pub fn get() -> &'static AppPaths {
+    APP_PATHS.get_or_init(|| {
+        let paths = AppPaths::resolve(env::args().skip(1)).expect("Failed to resolve app paths");
+        if let Err(e) = paths.ensure_dirs() {
+            println!("{}", e);
+        }
+        paths
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn resolve(
+        cli: &[&str],
+        env_root: Option<&str>,
+        exe_dir: &Path,
+        development: bool,
+    ) -> AppPaths {
+        AppPaths::resolve_from(
+            args(cli),
+            env_root.map(str::to_string),
+            Some(exe_dir.to_path_buf()),
+            development,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_the_data_dir_flag() {
+        assert_eq!(
+            data_dir_arg(&args(&["--data-dir", "/tmp/a"])).unwrap(),
+            Some(PathBuf::from("/tmp/a"))
+        );
+        assert_eq!(
+            data_dir_arg(&args(&["--portable", "--data-dir=/tmp/b"])).unwrap(),
+            Some(PathBuf::from("/tmp/b"))
+        );
+        assert_eq!(data_dir_arg(&args(&["--portable"])).unwrap(), None);
+        assert!(data_dir_arg(&args(&["--data-dir"])).is_err());
+    }
+
+    #[test]
+    fn resolves_the_root_in_order_of_precedence() {
+        let exe_dir = env::temp_dir().join("kaleidoswap-app-paths-test");
+        fs::create_dir_all(&exe_dir).unwrap();
+        let _ = fs::remove_file(exe_dir.join(PORTABLE_MARKER));
+
+        let paths = resolve(
+            &["--data-dir=/tmp/cli", "--portable"],
+            Some("/tmp/env"),
+            &exe_dir,
+            true,
+        );
+        assert_eq!(paths.source, RootSource::CliFlag);
+        assert_eq!(
+            paths.db_path(),
+            PathBuf::from("/tmp/cli/db/database.sqlite")
+        );
+
+        let paths = resolve(&["--portable"], Some("/tmp/env"), &exe_dir, true);
+        assert_eq!(paths.source, RootSource::EnvVar);
+        assert_eq!(paths.log_dir, PathBuf::from("/tmp/env/logs"));
+
+        // A blank variable is ignored
+        let paths = resolve(&["--portable"], Some(" "), &exe_dir, true);
+        assert_eq!(paths.source, RootSource::Portable);
+        assert_eq!(paths.root, exe_dir.join(PORTABLE_DIR));
+
+        assert_eq!(
+            resolve(&[], None, &exe_dir, true).source,
+            RootSource::Development
+        );
+        fs::write(exe_dir.join(PORTABLE_MARKER), "").unwrap();
+        assert_eq!(
+            resolve(&[], None, &exe_dir, true).source,
+            RootSource::Portable
+        );
+        fs::remove_file(exe_dir.join(PORTABLE_MARKER)).unwrap();
+
+        let paths = resolve(&[], None, &exe_dir, false);
+        assert_eq!(paths.source, RootSource::Platform);
+        assert!(paths.root.ends_with(APP_IDENTIFIER));
+        assert_eq!(paths.data_dir, paths.root.join("data"));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::node_client::NodeClient;
+use crate::ports::PortPair;
+use crate::rgb_node::NodeProcess;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The node a background loop works on during one tick.
+pub struct RunningNode {
+    pub account_name: String,
+    pub ports: PortPair,
+}
+
+impl RunningNode {
+    /// Returns a client for the node, or `None` while it is starting or locked.
+    pub async fn unlocked_client(&self) -> Option<NodeClient> {
+        let client = NodeClient::local(&self.ports.daemon_listening_port).ok()?;
+        client.node_info().await.ok()?;
+        Some(client)
+    }
+}
+
+/// Waits for `delay`, then returns the running node, or `None` if no node is
+/// running. Background loops call this at the start of every iteration.
+pub async fn next_tick(node_process: &Mutex<NodeProcess>, delay: Duration) -> Option<RunningNode> {
+    tokio::time::sleep(delay).await;
+    let (account_name, ports) = node_process.lock().unwrap().running_node()?;
+    Some(RunningNode {
+        account_name,
+        ports,
+    })
+}
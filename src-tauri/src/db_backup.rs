@@ -0,0 +1,404 @@
+use crate::clock::now_secs;
+use crate::db::{self, Db};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of automatic backups kept next to the database.
+const ROLLING_BACKUPS_KEPT: usize = 10;
+/// Directory, next to the database file, holding the automatic backups.
+pub const ROLLING_BACKUP_DIR: &str = "backups";
+
+/// Tables (and, for Accounts, columns) a database must have to be usable.
+const REQUIRED_TABLES: &[&str] = &[
+    "Accounts",
+    "Makers",
+    "AccountSettings",
+    "NodeSessions",
+    "AppState",
+    "NetworkPresets",
+    "AccountAudit",
+    "SecretStoreKey",
+    "Secrets",
+    "NodeBackups",
+    "SeenNodeEvents",
+    "HistoryEntries",
+    "KnownAssets",
+    "SwapMakers",
+    "Labels",
+    "Peers",
+    "UtxoMaintenanceLog",
+    "LspOrders",
+];
+const REQUIRED_ACCOUNT_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "network",
+    "datapath",
+    "rpc_connection_url",
+    "node_url",
+    "indexer_url",
+    "proxy_endpoint",
+    "default_lsp_url",
+    "daemon_listening_port",
+    "ldk_peer_listening_port",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VerifyReport {
+    pub path: String,
+    pub integrity_ok: bool,
+    pub integrity_messages: Vec<String>,
+    pub schema_version: i64,
+    pub expected_schema_version: i64,
+    pub missing_tables: Vec<String>,
+    pub missing_columns: Vec<String>,
+    pub account_count: i64,
+}
+
+impl VerifyReport {
+    /// Whether the database can be used by this version of the app as is,
+    /// or after running the pending migrations.
+    pub fn is_valid(&self) -> bool {
+        self.integrity_ok
+            && self.schema_version <= self.expected_schema_version
+            && self.missing_columns.is_empty()
+            && (self.missing_tables.is_empty()
+                || self.schema_version < self.expected_schema_version)
+    }
+}
+
+fn app_db_path() -> Result<PathBuf, String> {
+    db::get()
+        .path()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "The database is not stored on disk".to_string())
+}
+
+/// Copies the database to `dest_path` using the SQLite online backup API,
+/// so it is consistent even while the app keeps using it.
+pub fn backup_database(dest_path: &str) -> Result<(), String> {
+    let db_path = app_db_path()?;
+    if Path::new(dest_path) == db_path {
+        return Err("The backup cannot overwrite the live database".to_string());
+    }
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    conn.backup(DatabaseName::Main, dest_path, None)
+        .map_err(|e| format!("Failed to back up database: {}", e))?;
+    println!("Database backed up to {}", dest_path);
+    Ok(())
+}
+
+/// Takes an automatic backup of an open database into the rolling backups
+/// directory and prunes the oldest ones.
+pub fn rolling_backup(conn: &Connection, dir: &Path, reason: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let mut path = dir.join(format!("database-{}-{}.sqlite", now_secs(), reason));
+    let mut suffix = 1;
+    while path.exists() {
+        path = dir.join(format!(
+            "database-{}-{}-{}.sqlite",
+            now_secs(),
+            reason,
+            suffix
+        ));
+        suffix += 1;
+    }
+    conn.backup(DatabaseName::Main, &path, None)
+        .map_err(|e| format!("Failed to back up database: {}", e))?;
+    println!(
+        "Automatic database backup ({}) written to {:?}",
+        reason, path
+    );
+
+    prune_rolling_backups(dir, ROLLING_BACKUPS_KEPT);
+    Ok(path)
+}
+
+// The automatic backups in `dir`, newest first. Pruning and listing share this
+// order so the backups shown are exactly the ones kept.
+fn rolling_backups(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut backups: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "sqlite"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    backups.sort_by(|a, b| b.cmp(a));
+    Ok(backups.into_iter().map(|(_, path)| path).collect())
+}
+
+fn prune_rolling_backups(dir: &Path, keep: usize) {
+    let Ok(backups) = rolling_backups(dir) else {
+        return;
+    };
+    for path in backups.into_iter().skip(keep) {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("Failed to remove old database backup {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Lists the automatic backups, newest first.
+pub fn list_rolling_backups() -> Result<Vec<String>, String> {
+    let Some(dir) = db::get().backup_dir().filter(|dir| dir.exists()) else {
+        return Ok(Vec::new());
+    };
+    Ok(rolling_backups(&dir)?
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Runs `PRAGMA integrity_check` and validates the schema of a database file.
+/// Without a path, the live database is checked.
+pub fn verify_database(path: Option<&str>) -> Result<VerifyReport, String> {
+    match path {
+        Some(path) => verify_file(path),
+        None => verify_file(&app_db_path()?.to_string_lossy()),
+    }
+}
+
+fn verify_file(path: &str) -> Result<VerifyReport, String> {
+    let path = path.to_string();
+    if !Path::new(&path).is_file() {
+        return Err(format!("Database file not found: {}", path));
+    }
+
+    let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let integrity_messages = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+    let integrity_ok = integrity_messages == ["ok"];
+
+    let schema_version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let tables = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    let missing_tables: Vec<String> = REQUIRED_TABLES
+        .iter()
+        .filter(|table| !tables.iter().any(|t| t == *table))
+        .map(|table| table.to_string())
+        .collect();
+
+    let (missing_columns, account_count) = if tables.iter().any(|t| t == "Accounts") {
+        let columns = conn
+            .prepare("SELECT name FROM pragma_table_info('Accounts')")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.to_string())?;
+        let missing = REQUIRED_ACCOUNT_COLUMNS
+            .iter()
+            .filter(|column| !columns.iter().any(|c| c == *column))
+            .map(|column| format!("Accounts.{}", column))
+            .collect();
+        let count = conn
+            .query_row("SELECT COUNT(*) FROM Accounts", [], |row| row.get(0))
+            .unwrap_or(0);
+        (missing, count)
+    } else {
+        (vec!["Accounts".to_string()], 0)
+    };
+
+    Ok(VerifyReport {
+        path,
+        integrity_ok,
+        integrity_messages,
+        schema_version,
+        expected_schema_version: db::schema_version(),
+        missing_tables,
+        missing_columns,
+        account_count,
+    })
+}
+
+/// Replaces the live database with a validated copy. The current database is
+/// backed up first, and pending migrations run on the restored data.
+pub fn restore_database(source_path: &str) -> Result<VerifyReport, String> {
+    restore_into(db::get(), source_path)
+}
+
+fn restore_into(db: &Db, source_path: &str) -> Result<VerifyReport, String> {
+    let db_path = db
+        .path()
+        .ok_or_else(|| "The database is not stored on disk".to_string())?;
+    let report = verify_file(source_path)?;
+    if !report.is_valid() {
+        return Err(format!(
+            "The selected file is not a valid database (integrity: {}, schema version {} of {}, missing: {})",
+            report.integrity_messages.join("; "),
+            report.schema_version,
+            report.expected_schema_version,
+            report
+                .missing_tables
+                .iter()
+                .chain(report.missing_columns.iter())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let mut conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    if let Some(backup_dir) = db.backup_dir() {
+        rolling_backup(&conn, &backup_dir, "pre-restore")?;
+    }
+
+    conn.restore(
+        DatabaseName::Main,
+        source_path,
+        None::<fn(rusqlite::backup::Progress)>,
+    )
+    .map_err(|e| format!("Failed to restore database: {}", e))?;
+    drop(conn);
+    println!("Database restored from {}", source_path);
+
+    // Bring the restored copy up to the current schema
+    db.init_schema().map_err(|e| e.to_string())?;
+    verify_file(&db_path.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::AccountFields;
+    use std::time::Duration;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "kaleidoswap-db-backup-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn db_with_account(dir: &Path, name: &str) -> Db {
+        let db = Db::open(dir.join("database.sqlite")).unwrap();
+        db.insert_account(
+            &AccountFields {
+                name: name.to_string(),
+                network: "Regtest".to_string(),
+                datapath: Some(format!("kaleidoswap-{}", name)),
+                rpc_connection_url: String::new(),
+                node_url: String::new(),
+                indexer_url: String::new(),
+                proxy_endpoint: String::new(),
+                default_lsp_url: String::new(),
+                maker_urls: String::new(),
+                default_maker_url: String::new(),
+                daemon_listening_port: "3001".to_string(),
+                ldk_peer_listening_port: "9735".to_string(),
+            },
+            "test",
+        )
+        .unwrap();
+        db
+    }
+
+    fn account_names(db: &Db) -> Vec<String> {
+        db.get_accounts()
+            .unwrap()
+            .into_iter()
+            .map(|account| account.name)
+            .collect()
+    }
+
+    #[test]
+    fn restore_replaces_the_database_and_keeps_a_backup() {
+        let dir = test_dir("restore");
+        let live = db_with_account(&dir.join("live"), "alice");
+        let source = db_with_account(&dir.join("source"), "bob");
+        let source_path = source.path().unwrap().to_string_lossy().to_string();
+
+        let report = restore_into(&live, &source_path).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.account_count, 1);
+        assert_eq!(account_names(&live), vec!["bob"]);
+
+        let backups = rolling_backups(&live.backup_dir().unwrap()).unwrap();
+        assert_eq!(backups.len(), 1);
+        let backup = Db::open(&backups[0]).unwrap();
+        assert_eq!(account_names(&backup), vec!["alice"]);
+    }
+
+    #[test]
+    fn corrupt_files_are_not_restored() {
+        let dir = test_dir("corrupt");
+        let live = db_with_account(&dir.join("live"), "alice");
+        let corrupt = dir.join("corrupt.sqlite");
+        fs::write(&corrupt, b"this is not a database").unwrap();
+
+        assert!(restore_into(&live, &corrupt.to_string_lossy()).is_err());
+        assert_eq!(account_names(&live), vec!["alice"]);
+    }
+
+    #[test]
+    fn newer_schema_versions_are_rejected() {
+        let dir = test_dir("schema");
+        let live = db_with_account(&dir.join("live"), "alice");
+        let newer = db_with_account(&dir.join("newer"), "bob");
+        let newer_path = newer.path().unwrap().to_string_lossy().to_string();
+        Connection::open(&newer_path)
+            .unwrap()
+            .pragma_update(None, "user_version", db::schema_version() + 1)
+            .unwrap();
+
+        let report = verify_file(&newer_path).unwrap();
+        assert!(report.integrity_ok);
+        assert!(!report.is_valid());
+        assert!(restore_into(&live, &newer_path).is_err());
+        assert_eq!(account_names(&live), vec!["alice"]);
+    }
+
+    #[test]
+    fn pruning_keeps_the_newest_backups_that_are_listed() {
+        let dir = test_dir("prune");
+        let start = SystemTime::now() - Duration::from_secs(3600);
+        for i in 0..5 {
+            let path = dir.join(format!("database-{}-test.sqlite", i));
+            let file = fs::File::create(&path).unwrap();
+            file.set_modified(start + Duration::from_secs(i)).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), b"kept").unwrap();
+
+        prune_rolling_backups(&dir, 3);
+        let kept: Vec<String> = rolling_backups(&dir)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                "database-4-test.sqlite",
+                "database-3-test.sqlite",
+                "database-2-test.sqlite",
+            ]
+        );
+        assert!(dir.join("notes.txt").exists());
+    }
+}
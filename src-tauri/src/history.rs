@@ -0,0 +1,254 @@
+use crate::clock::now_secs;
+use crate::db::{self, KnownAsset, NewHistoryEntry};
+use crate::node_client::{NodeClient, Payment, Swap, Transaction, Transfer};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a `HistoryUpdatedEvent` when a sync adds or changes entries.
+pub const HISTORY_UPDATED_EVENT: &str = "history-updated";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryUpdatedEvent {
+    pub account_name: String,
+    pub changed: usize,
+}
+
+const INCOMING_TRANSFER_KINDS: &[&str] = &["Issuance", "ReceiveBlind", "ReceiveWitness"];
+
+fn direction(incoming: bool) -> Option<String> {
+    Some(if incoming { "incoming" } else { "outgoing" }.to_string())
+}
+
+fn details<T: Serialize>(record: &T) -> serde_json::Value {
+    serde_json::to_value(record).unwrap_or(serde_json::Value::Null)
+}
+
+/// The amount of an outgoing transaction is what left the wallet without the
+/// fee, which is kept apart in the details.
+fn transaction_entry(tx: &Transaction, seen_at: i64) -> NewHistoryEntry {
+    let incoming = tx.received > tx.sent;
+    let amount = if incoming {
+        tx.received - tx.sent
+    } else {
+        (tx.sent - tx.received).saturating_sub(tx.fee)
+    };
+    NewHistoryEntry {
+        kind: "transaction".to_string(),
+        external_id: tx.txid.clone(),
+        asset_id: None,
+        direction: direction(incoming),
+        status: if tx.confirmation_time.is_some() {
+            "Confirmed"
+        } else {
+            "Unconfirmed"
+        }
+        .to_string(),
+        amount: amount as i64,
+        created_at: tx
+            .confirmation_time
+            .as_ref()
+            .map(|time| time.timestamp as i64)
+            .unwrap_or(seen_at),
+        details: details(tx),
+    }
+}
+
+// Entry amounts are in the units of the entry's asset: base units for an RGB
+// asset, whole sats for BTC. The exact millisats stay in the details.
+fn msat_to_sat(msat: u64) -> i64 {
+    (msat / 1_000) as i64
+}
+
+fn payment_entry(payment: &Payment, seen_at: i64) -> NewHistoryEntry {
+    let amount = match &payment.asset_id {
+        Some(_) => payment.asset_amount.unwrap_or(0) as i64,
+        None => msat_to_sat(payment.amt_msat.unwrap_or(0)),
+    };
+    NewHistoryEntry {
+        kind: "payment".to_string(),
+        external_id: payment.payment_hash.clone(),
+        asset_id: payment.asset_id.clone(),
+        direction: direction(payment.inbound),
+        status: payment.status.clone(),
+        amount,
+        created_at: payment.created_at.map(|t| t as i64).unwrap_or(seen_at),
+        details: details(payment),
+    }
+}
+
+fn transfer_entry(asset_id: &str, transfer: &Transfer) -> NewHistoryEntry {
+    NewHistoryEntry {
+        kind: "transfer".to_string(),
+        external_id: format!("{}:{}", asset_id, transfer.idx),
+        asset_id: Some(asset_id.to_string()),
+        direction: direction(INCOMING_TRANSFER_KINDS.contains(&transfer.kind.as_str())),
+        status: transfer.status.clone(),
+        amount: transfer.amount as i64,
+        created_at: transfer.created_at as i64,
+        details: details(transfer),
+    }
+}
+
+fn swap_entry(role: &str, swap: &Swap, seen_at: i64) -> NewHistoryEntry {
+    let mut details = details(swap);
+    if let Some(details) = details.as_object_mut() {
+        details.insert("role".to_string(), role.into());
+    }
+    NewHistoryEntry {
+        kind: "swap".to_string(),
+        external_id: format!("{}:{}", role, swap.payment_hash),
+        asset_id: swap.from_asset.clone(),
+        direction: None,
+        status: swap.status.clone(),
+        amount: match &swap.from_asset {
+            Some(_) => swap.qty_from as i64,
+            None => msat_to_sat(swap.qty_from),
+        },
+        created_at: swap.requested_at.map(|t| t as i64).unwrap_or(seen_at),
+        details,
+    }
+}
+
+/// Copies the node's transactions, payments, transfers and swaps into the
+/// local history store. Returns how many entries were added or changed.
+pub async fn sync(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+) -> Result<usize, String> {
+    let seen_at = now_secs();
+    let mut entries: Vec<NewHistoryEntry> = client
+        .list_transactions(true)
+        .await
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|tx| transaction_entry(tx, seen_at))
+        .collect();
+    entries.extend(
+        client
+            .list_payments()
+            .await
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|payment| payment_entry(payment, seen_at)),
+    );
+    let assets = client.list_assets().await.map_err(|e| e.to_string())?.nia;
+    let known_assets: Vec<KnownAsset> = assets
+        .iter()
+        .map(|asset| KnownAsset {
+            asset_id: asset.asset_id.clone(),
+            ticker: asset.ticker.clone(),
+            name: asset.name.clone(),
+            precision: asset.precision,
+        })
+        .collect();
+    db::get()
+        .upsert_known_assets(account_name, &known_assets)
+        .map_err(|e| e.to_string())?;
+    for asset in assets {
+        entries.extend(
+            client
+                .list_transfers(&asset.asset_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .iter()
+                .map(|transfer| transfer_entry(&asset.asset_id, transfer)),
+        );
+    }
+    let swaps = client.list_swaps().await.map_err(|e| e.to_string())?;
+    entries.extend(
+        swaps
+            .maker
+            .iter()
+            .map(|swap| swap_entry("maker", swap, seen_at)),
+    );
+    entries.extend(
+        swaps
+            .taker
+            .iter()
+            .map(|swap| swap_entry("taker", swap, seen_at)),
+    );
+
+    let changed = db::get()
+        .upsert_history_entries(account_name, &entries)
+        .map_err(|e| e.to_string())?;
+    if changed > 0 {
+        let _ = app_handle.emit(
+            HISTORY_UPDATED_EVENT,
+            HistoryUpdatedEvent {
+                account_name: account_name.to_string(),
+                changed,
+            },
+        );
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(received: u64, sent: u64, fee: u64) -> Transaction {
+        Transaction {
+            transaction_type: "User".to_string(),
+            txid: "txid".to_string(),
+            received,
+            sent,
+            fee,
+            confirmation_time: None,
+        }
+    }
+
+    #[test]
+    fn transaction_amounts_exclude_the_fee() {
+        // 100_000 in, 60_000 paid out with 39_500 change and a 500 fee
+        let outgoing = transaction_entry(&transaction(39_500, 100_000, 500), 0);
+        assert_eq!(outgoing.direction.as_deref(), Some("outgoing"));
+        assert_eq!(outgoing.amount, 60_000);
+
+        let incoming = transaction_entry(&transaction(25_000, 0, 300), 0);
+        assert_eq!(incoming.direction.as_deref(), Some("incoming"));
+        assert_eq!(incoming.amount, 25_000);
+    }
+
+    #[test]
+    fn amounts_are_in_the_units_of_the_entry_asset() {
+        let payment = Payment {
+            amt_msat: Some(3_000_500),
+            asset_amount: None,
+            asset_id: None,
+            payment_hash: "hash".to_string(),
+            inbound: true,
+            payee_pubkey: None,
+            status: "Succeeded".to_string(),
+            created_at: None,
+            updated_at: None,
+        };
+        assert_eq!(payment_entry(&payment, 0).amount, 3_000);
+
+        let asset_payment = Payment {
+            asset_amount: Some(42),
+            asset_id: Some("rgb:usdt".to_string()),
+            ..payment
+        };
+        let entry = payment_entry(&asset_payment, 0);
+        assert_eq!(entry.asset_id.as_deref(), Some("rgb:usdt"));
+        assert_eq!(entry.amount, 42);
+
+        let swap = Swap {
+            payment_hash: "hash".to_string(),
+            qty_from: 3_000_000,
+            qty_to: 1_500_000,
+            from_asset: None,
+            to_asset: Some("rgb:usdt".to_string()),
+            status: "Succeeded".to_string(),
+            requested_at: None,
+            initiated_at: None,
+            expires_at: None,
+            completed_at: None,
+        };
+        let entry = swap_entry("taker", &swap, 0);
+        assert_eq!(entry.asset_id, None);
+        assert_eq!(entry.amount, 3_000);
+    }
+}
@@ -0,0 +1,412 @@
+use crate::db::{self, HistoryEntry, KnownAsset, Label};
+use crate::labels;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One line of an accounting export. Amounts are decimal strings so no
+/// precision is lost; empty strings stand for "not applicable".
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct ExportRow {
+    pub account: String,
+    /// `transaction`, `payment`, `transfer` or `swap`.
+    pub kind: String,
+    pub id: String,
+    /// UTC, RFC 3339.
+    pub date: String,
+    pub timestamp: i64,
+    /// `incoming` or `outgoing`, or the role (`maker`/`taker`) for swaps.
+    pub direction: String,
+    pub status: String,
+    /// BTC amount in sats, with millisat decimals for lightning payments.
+    /// Never includes the fee, so `amount_sat + fee_sat` is what an outgoing
+    /// transaction cost.
+    pub amount_sat: String,
+    /// On-chain fee, only set for outgoing transactions since the sender pays it.
+    pub fee_sat: String,
+    pub asset_id: String,
+    pub asset_ticker: String,
+    /// Asset amount in display units, using the asset's precision.
+    pub asset_amount: String,
+    /// Received side of a swap.
+    pub to_asset_id: String,
+    pub to_asset_ticker: String,
+    pub to_asset_amount: String,
+    /// Payee pubkey of lightning payments, recipient ID of RGB transfers.
+    pub counterparty: String,
+    pub maker: String,
+    /// The entry's label followed by its `#tags`.
+    pub labels: String,
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "account",
+    "kind",
+    "id",
+    "date",
+    "timestamp",
+    "direction",
+    "status",
+    "amount_sat",
+    "fee_sat",
+    "asset_id",
+    "asset_ticker",
+    "asset_amount",
+    "to_asset_id",
+    "to_asset_ticker",
+    "to_asset_amount",
+    "counterparty",
+    "maker",
+    "labels",
+];
+
+impl ExportRow {
+    fn csv_values(&self) -> Vec<String> {
+        vec![
+            self.account.clone(),
+            self.kind.clone(),
+            self.id.clone(),
+            self.date.clone(),
+            self.timestamp.to_string(),
+            self.direction.clone(),
+            self.status.clone(),
+            self.amount_sat.clone(),
+            self.fee_sat.clone(),
+            self.asset_id.clone(),
+            self.asset_ticker.clone(),
+            self.asset_amount.clone(),
+            self.to_asset_id.clone(),
+            self.to_asset_ticker.clone(),
+            self.to_asset_amount.clone(),
+            self.counterparty.clone(),
+            self.maker.clone(),
+            self.labels.clone(),
+        ]
+    }
+}
+
+/// Formats unix seconds as an RFC 3339 UTC date.
+fn rfc3339(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+/// Writes `raw` base units as a decimal with `precision` fractional digits.
+fn format_units(raw: u64, precision: u8) -> String {
+    if precision == 0 {
+        return raw.to_string();
+    }
+    let divisor = 10u64.pow(precision as u32);
+    format!(
+        "{}.{:0width$}",
+        raw / divisor,
+        raw % divisor,
+        width = precision as usize
+    )
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(rows: &[ExportRow]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for row in rows {
+        let values: Vec<String> = row.csv_values().iter().map(|v| csv_field(v)).collect();
+        csv.push_str(&values.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn detail_str(details: &Value, key: &str) -> Option<String> {
+    details.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn detail_u64(details: &Value, key: &str) -> Option<u64> {
+    details.get(key).and_then(Value::as_u64)
+}
+
+/// Writes millisats as BTC with 8 decimals, keeping 3 more digits for a
+/// fraction of a sat.
+fn format_msat_as_btc(msat: u64) -> String {
+    let btc = format_units(msat, 11);
+    match btc.strip_suffix("000") {
+        Some(whole_sats) => whole_sats.to_string(),
+        None => btc,
+    }
+}
+
+/// `(asset_id, ticker, amount)` of an asset amount. Without an asset ID the
+/// amount is BTC in millisats, as in swaps.
+fn asset_leg(
+    asset_id: Option<&str>,
+    raw: u64,
+    assets: &HashMap<String, KnownAsset>,
+) -> (String, String, String) {
+    match asset_id {
+        None => (String::new(), "BTC".to_string(), format_msat_as_btc(raw)),
+        Some(asset_id) => match assets.get(asset_id) {
+            Some(asset) => (
+                asset_id.to_string(),
+                asset.ticker.clone(),
+                format_units(raw, asset.precision),
+            ),
+            None => (asset_id.to_string(), String::new(), raw.to_string()),
+        },
+    }
+}
+
+fn export_row(
+    account_name: &str,
+    entry: &HistoryEntry,
+    assets: &HashMap<String, KnownAsset>,
+    makers: &HashMap<String, String>,
+    labels: &HashMap<(String, String), Label>,
+) -> ExportRow {
+    let details = &entry.details;
+    let amount = entry.amount.max(0) as u64;
+    let mut row = ExportRow {
+        account: account_name.to_string(),
+        kind: entry.kind.clone(),
+        id: entry.external_id.clone(),
+        date: rfc3339(entry.created_at),
+        timestamp: entry.created_at,
+        direction: entry.direction.clone().unwrap_or_default(),
+        status: entry.status.clone(),
+        ..ExportRow::default()
+    };
+    match entry.kind.as_str() {
+        "transaction" => {
+            row.amount_sat = amount.to_string();
+            if row.direction == "outgoing" {
+                row.fee_sat = detail_u64(details, "fee")
+                    .map(|fee| fee.to_string())
+                    .unwrap_or_default();
+            }
+        }
+        "payment" => {
+            row.amount_sat = format_units(detail_u64(details, "amt_msat").unwrap_or(0), 3);
+            if let (Some(asset_id), Some(asset_amount)) = (
+                detail_str(details, "asset_id"),
+                detail_u64(details, "asset_amount"),
+            ) {
+                (row.asset_id, row.asset_ticker, row.asset_amount) =
+                    asset_leg(Some(&asset_id), asset_amount, assets);
+            }
+            row.counterparty = detail_str(details, "payee_pubkey").unwrap_or_default();
+        }
+        "transfer" => {
+            (row.asset_id, row.asset_ticker, row.asset_amount) =
+                asset_leg(entry.asset_id.as_deref(), amount, assets);
+            row.counterparty = detail_str(details, "recipient_id").unwrap_or_default();
+        }
+        "swap" => {
+            (row.asset_id, row.asset_ticker, row.asset_amount) = asset_leg(
+                detail_str(details, "from_asset").as_deref(),
+                detail_u64(details, "qty_from").unwrap_or(0),
+                assets,
+            );
+            (row.to_asset_id, row.to_asset_ticker, row.to_asset_amount) = asset_leg(
+                detail_str(details, "to_asset").as_deref(),
+                detail_u64(details, "qty_to").unwrap_or(0),
+                assets,
+            );
+            row.direction = detail_str(details, "role").unwrap_or_default();
+            row.maker = detail_str(details, "payment_hash")
+                .and_then(|payment_hash| makers.get(&payment_hash).cloned())
+                .unwrap_or_default();
+        }
+        _ => {}
+    }
+    let label_key = match entry.kind.as_str() {
+        "transaction" => Some(("tx", entry.external_id.clone())),
+        "payment" => Some(("payment", entry.external_id.clone())),
+        "transfer" => Some(("transfer", entry.external_id.clone())),
+        "swap" => detail_str(details, "payment_hash").map(|hash| ("payment", hash)),
+        _ => None,
+    };
+    row.labels = label_key
+        .and_then(|(label_type, reference)| labels.get(&(label_type.to_string(), reference)))
+        .map(labels::display)
+        .unwrap_or_default();
+    row
+}
+
+/// Export rows of the given accounts' history within the inclusive date
+/// bounds, oldest first.
+pub fn export_rows(
+    account_names: &[String],
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<ExportRow>, String> {
+    let db = db::get();
+    let mut rows = Vec::new();
+    for account_name in account_names {
+        if db
+            .get_account_by_name(account_name)
+            .map_err(|e| e.to_string())?
+            .is_none()
+        {
+            return Err(format!("Account not found: {}", account_name));
+        }
+        let assets: HashMap<String, KnownAsset> = db
+            .get_known_assets(account_name)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|asset| (asset.asset_id.clone(), asset))
+            .collect();
+        let makers = db
+            .get_swap_makers(account_name)
+            .map_err(|e| e.to_string())?;
+        let labels = labels::label_index(account_name)?;
+        for entry in db
+            .get_history_range(account_name, from, to)
+            .map_err(|e| e.to_string())?
+        {
+            rows.push(export_row(account_name, &entry, &assets, &makers, &labels));
+        }
+    }
+    rows.sort_by_key(|row| row.timestamp);
+    Ok(rows)
+}
+
+/// Writes the history of the given accounts to `path` as CSV or JSON.
+/// Returns the number of rows written.
+pub fn export_history(
+    account_names: &[String],
+    path: &Path,
+    format: ExportFormat,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<usize, String> {
+    let rows = export_rows(account_names, from, to)?;
+    let contents = match format {
+        ExportFormat::Csv => to_csv(&rows),
+        ExportFormat::Json => serde_json::to_string_pretty(&rows).map_err(|e| e.to_string())?,
+    };
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn formats_dates_and_amounts() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(1_700_000_000), "2023-11-14T22:13:20Z");
+
+        assert_eq!(format_units(1_234_567, 3), "1234.567");
+        assert_eq!(format_units(5, 2), "0.05");
+        assert_eq!(format_units(42, 0), "42");
+        assert_eq!(format_msat_as_btc(123_456_789_000), "1.23456789");
+        assert_eq!(format_msat_as_btc(1_500), "0.00000001500");
+    }
+
+    #[test]
+    fn escapes_csv_fields() {
+        let rows = vec![ExportRow {
+            account: "alice".to_string(),
+            labels: "rent, \"march\"".to_string(),
+            ..ExportRow::default()
+        }];
+        let csv = to_csv(&rows);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap().split(',').count(), CSV_COLUMNS.len());
+        assert!(lines.next().unwrap().ends_with(",\"rent, \"\"march\"\"\""));
+    }
+
+    #[test]
+    fn swap_rows_use_asset_precision_and_maker() {
+        let assets = HashMap::from([(
+            "rgb:usdt".to_string(),
+            KnownAsset {
+                asset_id: "rgb:usdt".to_string(),
+                ticker: "USDT".to_string(),
+                name: "Tether".to_string(),
+                precision: 6,
+            },
+        )]);
+        let makers = HashMap::from([("hash".to_string(), "https://maker.example".to_string())]);
+        let entry = HistoryEntry {
+            id: 1,
+            kind: "swap".to_string(),
+            external_id: "taker:hash".to_string(),
+            asset_id: None,
+            direction: None,
+            status: "Succeeded".to_string(),
+            amount: 3_000,
+            created_at: 0,
+            updated_at: 0,
+            details: json!({
+                "payment_hash": "hash",
+                "qty_from": 3_000_000,
+                "qty_to": 1_500_000,
+                "from_asset": null,
+                "to_asset": "rgb:usdt",
+                "role": "taker",
+            }),
+        };
+
+        let labels = HashMap::from([(
+            ("payment".to_string(), "hash".to_string()),
+            Label {
+                label_type: "payment".to_string(),
+                reference: "hash".to_string(),
+                label: "Rebalance".to_string(),
+                tags: vec!["ops".to_string()],
+                origin: None,
+                spendable: None,
+            },
+        )]);
+        let row = export_row("alice", &entry, &assets, &makers, &labels);
+        assert_eq!(row.direction, "taker");
+        assert_eq!(
+            (row.asset_ticker.as_str(), row.asset_amount.as_str()),
+            ("BTC", "0.00003000")
+        );
+        assert_eq!(
+            (row.to_asset_ticker.as_str(), row.to_asset_amount.as_str()),
+            ("USDT", "1.500000")
+        );
+        assert_eq!(row.maker, "https://maker.example");
+        assert_eq!(row.labels, "Rebalance #ops");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::db::{self, Label};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Record types defined by BIP-329.
+const BIP329_TYPES: &[&str] = &["tx", "addr", "pubkey", "input", "output", "xpub"];
+/// The app's own types. Other wallets ignore them on import.
+/// Transfers are referenced as `<asset_id>:<idx>`, like in the history store.
+const APP_TYPES: &[&str] = &["payment", "transfer", "channel", "invoice"];
+
+/// BIP-329 recommends importers truncate longer labels.
+const MAX_LABEL_CHARS: usize = 255;
+const MAX_TAGS: usize = 20;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LabelImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    /// One message per skipped line.
+    pub errors: Vec<String>,
+}
+
+/// Checks the type and reference and tidies the text and tags of a label.
+pub fn normalize(mut label: Label) -> Result<Label, String> {
+    label.label_type = label.label_type.trim().to_lowercase();
+    if !BIP329_TYPES.contains(&label.label_type.as_str())
+        && !APP_TYPES.contains(&label.label_type.as_str())
+    {
+        return Err(format!("Unknown label type: {}", label.label_type));
+    }
+    label.reference = label.reference.trim().to_string();
+    if label.reference.is_empty() {
+        return Err("The label reference can't be empty".to_string());
+    }
+    label.label = label.label.trim().chars().take(MAX_LABEL_CHARS).collect();
+    let mut tags: Vec<String> = Vec::new();
+    for tag in label.tags.iter().map(|tag| tag.trim().to_lowercase()) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.len() > MAX_TAGS {
+        return Err(format!("A label can have at most {} tags", MAX_TAGS));
+    }
+    label.tags = tags;
+    // `spendable` only means something for outputs
+    if label.label_type != "output" {
+        label.spendable = None;
+    }
+    Ok(label)
+}
+
+/// Serializes labels as BIP-329 JSON lines. Tags are written as an extra
+/// `tags` field, which other wallets ignore.
+fn to_bip329(labels: &[Label]) -> String {
+    labels
+        .iter()
+        .filter_map(|label| {
+            let mut record = serde_json::to_value(label).ok()?;
+            let fields = record.as_object_mut()?;
+            fields.retain(|_, value| !value.is_null());
+            if label.tags.is_empty() {
+                fields.remove("tags");
+            }
+            Some(format!("{}\n", record))
+        })
+        .collect()
+}
+
+/// Parses BIP-329 JSON lines, collecting an error for each unusable line.
+fn parse_bip329(contents: &str) -> (Vec<Label>, Vec<String>) {
+    let mut labels = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut record: serde_json::Value = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(format!("Line {}: {}", index + 1, e));
+                continue;
+            }
+        };
+        // The label is optional in BIP-329
+        if let Some(fields) = record.as_object_mut() {
+            fields.entry("label").or_insert_with(|| "".into());
+        }
+        match serde_json::from_value::<Label>(record)
+            .map_err(|e| e.to_string())
+            .and_then(normalize)
+        {
+            Ok(label) => labels.push(label),
+            Err(e) => errors.push(format!("Line {}: {}", index + 1, e)),
+        }
+    }
+    (labels, errors)
+}
+
+/// Writes all labels of an account to `path` in BIP-329 format.
+/// Returns the number of records written.
+pub fn export_labels(account_name: &str, path: &Path) -> Result<usize, String> {
+    let labels = db::get()
+        .search_labels(account_name, None, None)
+        .map_err(|e| e.to_string())?;
+    fs::write(path, to_bip329(&labels))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(labels.len())
+}
+
+/// Imports a BIP-329 file into an account. Records replace existing labels
+/// with the same type and reference.
+pub fn import_labels(account_name: &str, path: &Path) -> Result<LabelImportReport, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let (labels, errors) = parse_bip329(&contents);
+    db::get()
+        .set_labels(account_name, &labels)
+        .map_err(|e| e.to_string())?;
+    Ok(LabelImportReport {
+        imported: labels.len(),
+        skipped: errors.len(),
+        errors,
+    })
+}
+
+/// Labels of an account by `(type, ref)`, for annotating exports.
+pub fn label_index(account_name: &str) -> Result<HashMap<(String, String), Label>, String> {
+    Ok(db::get()
+        .search_labels(account_name, None, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|label| ((label.label_type.clone(), label.reference.clone()), label))
+        .collect())
+}
+
+/// A label and its tags as one line of text, e.g. `Rent #housing`.
+pub fn display(label: &Label) -> String {
+    std::iter::once(label.label.clone())
+        .chain(label.tags.iter().map(|tag| format!("#{}", tag)))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_bip329_records() {
+        let contents = r#"{"type":"tx","ref":"f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd","label":"Transaction","origin":"wpkh([d0d3f4a6/84'/0'/0'])"}
+{"type":"output","ref":"f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd:1","label":"Change","spendable":false}
+{"type":"addr","ref":"bc1q34aq5drpuwy3wgl9lhup9892qp6svr8ldzyy7c"}
+{"type":"channel","ref":"cccc","label":"LSP","tags":["Liquidity"," liquidity",""]}
+
+not json
+{"type":"utxo","ref":"x","label":"?"}
+{"type":"tx","ref":" ","label":"empty"}
+"#;
+        let (labels, errors) = parse_bip329(contents);
+        assert_eq!(labels.len(), 4);
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("Line 6:"));
+        assert_eq!(labels[1].spendable, Some(false));
+        assert_eq!(labels[2].label, "");
+        assert_eq!(labels[3].tags, vec!["liquidity"]);
+
+        let exported = to_bip329(&labels);
+        assert!(!exported.contains("null"));
+        assert!(exported.lines().next().unwrap().contains("\"origin\""));
+        let (reparsed, errors) = parse_bip329(&exported);
+        assert!(errors.is_empty());
+        assert_eq!(reparsed, labels);
+    }
+
+    #[test]
+    fn normalizes_labels() {
+        let label = normalize(Label {
+            label_type: " TX ".to_string(),
+            reference: "aaaa".to_string(),
+            label: "x".repeat(300),
+            tags: Vec::new(),
+            origin: None,
+            spendable: Some(true),
+        })
+        .unwrap();
+        assert_eq!(label.label_type, "tx");
+        assert_eq!(label.label.chars().count(), MAX_LABEL_CHARS);
+        assert_eq!(label.spendable, None);
+        assert_eq!(display(&label).len(), MAX_LABEL_CHARS);
+    }
+}
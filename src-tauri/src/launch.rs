@@ -0,0 +1,121 @@
+use crate::account_settings;
+use crate::db::{self, Account};
+use crate::rgb_node::NodeProcess;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// AppState key holding the name of the last selected account.
+pub const CURRENT_ACCOUNT_KEY: &str = "current_account";
+
+/// Emitted with a short status message while launch tasks run.
+pub const LAUNCH_PROGRESS_EVENT: &str = "launch-progress";
+
+/// Tracks whether launch tasks (like auto-starting the node) are still running,
+/// so the splashscreen stays up until they are done.
+#[derive(Default)]
+pub struct LaunchState {
+    in_progress: AtomicBool,
+}
+
+impl LaunchState {
+    pub fn is_in_progress(&self) -> bool {
+        self.in_progress.load(Ordering::SeqCst)
+    }
+}
+
+/// Loads the account that was selected when the app was last closed.
+/// A persisted name that no longer matches an account is dropped.
+pub fn restore_current_account() -> Option<Account> {
+    let name = match db::get().get_app_state(CURRENT_ACCOUNT_KEY) {
+        Ok(Some(name)) => name,
+        Ok(None) => return None,
+        Err(e) => {
+            println!("Failed to read the persisted current account: {}", e);
+            return None;
+        }
+    };
+
+    match db::get().get_account_by_name(&name) {
+        Ok(Some(account)) => {
+            println!("Restored current account: {}", name);
+            Some(account)
+        }
+        Ok(None) => {
+            println!("Persisted current account {} no longer exists", name);
+            let _ = db::get().delete_app_state(CURRENT_ACCOUNT_KEY);
+            None
+        }
+        Err(e) => {
+            println!("Failed to load the persisted current account: {}", e);
+            None
+        }
+    }
+}
+
+/// Shows a status message on the splashscreen and emits it to the frontend.
+pub fn report_progress(app_handle: &AppHandle, message: impl AsRef<str>) {
+    let message = message.as_ref();
+    println!("Launch: {}", message);
+    let _ = app_handle.emit(LAUNCH_PROGRESS_EVENT, message);
+    if let Some(splashscreen) = app_handle.get_webview_window("splashscreen") {
+        if let Ok(message) = serde_json::to_string(message) {
+            let _ = splashscreen.eval(&format!(
+                "window.setLaunchStatus && window.setLaunchStatus({})",
+                message
+            ));
+        }
+    }
+}
+
+/// Starts the node of the restored account in the background if the account
+/// opted in with the `start_node_on_launch` setting. Any failure (missing
+/// binary, busy ports, ...) is reported and the app continues without a node.
+pub fn auto_start_node(
+    app_handle: AppHandle,
+    node_process: Arc<Mutex<NodeProcess>>,
+    account: Account,
+) {
+    if !account.is_local() {
+        return;
+    }
+    if !account_settings::get_bool(&account.name, "start_node_on_launch").unwrap_or(false) {
+        return;
+    }
+
+    app_handle
+        .state::<LaunchState>()
+        .in_progress
+        .store(true, Ordering::SeqCst);
+
+    std::thread::spawn(move || {
+        report_progress(&app_handle, format!("Starting node for {}", account.name));
+
+        let result = node_process.lock().unwrap().start(
+            account.network.clone(),
+            account.datapath.clone(),
+            account.daemon_listening_port.clone(),
+            account.ldk_peer_listening_port.clone(),
+            account.name.clone(),
+        );
+
+        match result {
+            Ok(ports) => report_progress(
+                &app_handle,
+                format!("Node started on port {}", ports.daemon_listening_port),
+            ),
+            Err(e) => {
+                println!("Failed to start node on launch: {}", e);
+                report_progress(
+                    &app_handle,
+                    "Could not start the node, continuing without it",
+                );
+            }
+        }
+
+        app_handle
+            .state::<LaunchState>()
+            .in_progress
+            .store(false, Ordering::SeqCst);
+    });
+}
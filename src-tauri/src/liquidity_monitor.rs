@@ -0,0 +1,572 @@
+use crate::clock::now_secs;
+use crate::lsp_client::{CreateOrderRequest, LspClient, Order};
+use crate::node_client::{Channel, NodeClient};
+use crate::rgb_node::NodeProcess;
+use crate::{account_settings, background, db};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a `LiquidityAlertEvent` when a channel's liquidity crosses a
+/// threshold, in either direction.
+pub const LIQUIDITY_ALERT_EVENT: &str = "liquidity-alert";
+/// Emitted with an `LspOrderEvent` when an automatic LSP order is placed or
+/// its state changes.
+pub const LSP_ORDER_EVENT: &str = "lsp-order-changed";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Minimum time between two automatic orders, so a failing LSP isn't asked
+/// for a new channel every minute.
+const ORDER_COOLDOWN_SECS: i64 = 6 * 60 * 60;
+/// About six months.
+const CHANNEL_EXPIRY_BLOCKS: u32 = 6 * 30 * 144;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityKind {
+    /// BTC the channel can receive.
+    Inbound,
+    /// BTC the channel can send.
+    Outbound,
+    /// RGB asset amount the channel can receive.
+    AssetInbound,
+    /// RGB asset amount the channel can send.
+    AssetOutbound,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LiquidityAlertEvent {
+    pub account_name: String,
+    pub channel_id: String,
+    pub peer_pubkey: String,
+    pub kind: LiquidityKind,
+    pub asset_id: Option<String>,
+    /// Sats, or asset units for the asset kinds.
+    pub available: u64,
+    pub threshold: u64,
+    /// False once the liquidity is back above the threshold.
+    pub low: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LspOrderEvent {
+    pub account_name: String,
+    pub order: db::LspOrder,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Thresholds {
+    alerts: bool,
+    min_inbound_sat: u64,
+    min_outbound_sat: u64,
+    min_asset_percent: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OrderPolicy {
+    lsp_url: String,
+    inbound_sat: u64,
+    max_fee_sat: u64,
+}
+
+type LowLiquidity = HashMap<(String, LiquidityKind), LiquidityAlertEvent>;
+
+fn load_thresholds(account_name: &str) -> Result<Thresholds, String> {
+    Ok(Thresholds {
+        alerts: account_settings::get_bool(account_name, "liquidity_alerts")?,
+        min_inbound_sat: account_settings::get_i64(account_name, "liquidity_min_inbound_sat")?
+            as u64,
+        min_outbound_sat: account_settings::get_i64(account_name, "liquidity_min_outbound_sat")?
+            as u64,
+        min_asset_percent: account_settings::get_i64(account_name, "liquidity_min_asset_percent")?
+            as u64,
+    })
+}
+
+/// The account's automatic order policy, `None` if it is off or no default
+/// LSP is configured.
+fn load_order_policy(account_name: &str) -> Result<Option<OrderPolicy>, String> {
+    if !account_settings::get_bool(account_name, "lsp_auto_order")? {
+        return Ok(None);
+    }
+    let lsp_url = db::get()
+        .get_account_by_name(account_name)
+        .map_err(|e| e.to_string())?
+        .map(|account| account.default_lsp_url.trim().to_string())
+        .unwrap_or_default();
+    if lsp_url.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(OrderPolicy {
+        lsp_url,
+        inbound_sat: account_settings::get_i64(account_name, "lsp_order_inbound_sat")? as u64,
+        max_fee_sat: account_settings::get_i64(account_name, "lsp_order_max_fee_sat")? as u64,
+    }))
+}
+
+/// Liquidity below the thresholds in the ready channels, by channel and kind.
+/// A threshold of zero is never crossed.
+fn low_liquidity(
+    account_name: &str,
+    channels: &[Channel],
+    thresholds: &Thresholds,
+) -> LowLiquidity {
+    let mut low = LowLiquidity::new();
+    for channel in channels.iter().filter(|channel| channel.ready) {
+        let mut check = |kind, asset_id: Option<&String>, available: u64, threshold: u64| {
+            if available < threshold {
+                low.insert(
+                    (channel.channel_id.clone(), kind),
+                    LiquidityAlertEvent {
+                        account_name: account_name.to_string(),
+                        channel_id: channel.channel_id.clone(),
+                        peer_pubkey: channel.peer_pubkey.clone(),
+                        kind,
+                        asset_id: asset_id.cloned(),
+                        available,
+                        threshold,
+                        low: true,
+                    },
+                );
+            }
+        };
+        check(
+            LiquidityKind::Inbound,
+            None,
+            channel.inbound_balance_msat / 1000,
+            thresholds.min_inbound_sat,
+        );
+        check(
+            LiquidityKind::Outbound,
+            None,
+            channel.outbound_balance_msat / 1000,
+            thresholds.min_outbound_sat,
+        );
+        if let Some(asset_id) = &channel.asset_id {
+            let local = channel.asset_local_amount.unwrap_or(0);
+            let remote = channel.asset_remote_amount.unwrap_or(0);
+            let threshold = (local + remote) * thresholds.min_asset_percent / 100;
+            check(
+                LiquidityKind::AssetInbound,
+                Some(asset_id),
+                remote,
+                threshold,
+            );
+            check(
+                LiquidityKind::AssetOutbound,
+                Some(asset_id),
+                local,
+                threshold,
+            );
+        }
+    }
+    low
+}
+
+/// Alerts for liquidity that became low since the previous check, and for
+/// liquidity that recovered in channels that are still open.
+fn crossings(
+    previous: &LowLiquidity,
+    current: &LowLiquidity,
+    channels: &[Channel],
+) -> Vec<LiquidityAlertEvent> {
+    let mut alerts: Vec<LiquidityAlertEvent> = current
+        .iter()
+        .filter(|(key, _)| !previous.contains_key(*key))
+        .map(|(_, alert)| alert.clone())
+        .collect();
+    for ((channel_id, kind), alert) in previous {
+        if current.contains_key(&(channel_id.clone(), *kind)) {
+            continue;
+        }
+        let Some(channel) = channels
+            .iter()
+            .find(|channel| channel.channel_id == *channel_id && channel.ready)
+        else {
+            continue;
+        };
+        let available = match kind {
+            LiquidityKind::Inbound => channel.inbound_balance_msat / 1000,
+            LiquidityKind::Outbound => channel.outbound_balance_msat / 1000,
+            LiquidityKind::AssetInbound => channel.asset_remote_amount.unwrap_or(0),
+            LiquidityKind::AssetOutbound => channel.asset_local_amount.unwrap_or(0),
+        };
+        alerts.push(LiquidityAlertEvent {
+            available,
+            low: false,
+            ..alert.clone()
+        });
+    }
+    alerts.sort_by(|a, b| (&a.channel_id, a.low).cmp(&(&b.channel_id, b.low)));
+    alerts
+}
+
+/// Sats all usable channels can receive together.
+fn total_inbound_sat(channels: &[Channel]) -> u64 {
+    channels
+        .iter()
+        .filter(|channel| channel.is_usable)
+        .map(|channel| channel.inbound_balance_msat / 1000)
+        .sum()
+}
+
+/// Whether a new automatic order may be placed, given the account's orders
+/// (newest first).
+fn may_order(orders: &[db::LspOrder], now: i64) -> bool {
+    let open = orders
+        .iter()
+        .any(|order| order.order_state != "COMPLETED" && order.order_state != "FAILED");
+    let recent = orders
+        .first()
+        .is_some_and(|order| now - order.created_at < ORDER_COOLDOWN_SECS);
+    !open && !recent
+}
+
+/// Orders an inbound channel from the LSP and pays for it if the fee is
+/// within the policy's limit.
+async fn place_order(
+    client: &NodeClient,
+    account_name: &str,
+    policy: &OrderPolicy,
+) -> Result<db::LspOrder, String> {
+    let lsp = LspClient::new(&policy.lsp_url)?;
+    let info = lsp.get_info().await?;
+    let node_info = client.node_info().await.map_err(|e| e.to_string())?;
+
+    // The LSP can only open the channel to a connected peer
+    if let Some(connection_url) = &info.lsp_connection_url {
+        let lsp_pubkey = connection_url.split('@').next().unwrap_or_default();
+        let peers = client.list_peers().await.map_err(|e| e.to_string())?;
+        if !peers.iter().any(|peer| peer.pubkey == lsp_pubkey) {
+            client
+                .connect_peer(connection_url)
+                .await
+                .map_err(|e| format!("Failed to connect to the LSP: {}", e))?;
+        }
+    }
+
+    let options = &info.options;
+    let order = lsp
+        .create_order(&CreateOrderRequest {
+            client_pubkey: node_info.pubkey,
+            lsp_balance_sat: policy
+                .inbound_sat
+                .max(options.min_initial_lsp_balance_sat)
+                .min(options.max_initial_lsp_balance_sat),
+            client_balance_sat: 0,
+            required_channel_confirmations: options.min_required_channel_confirmations,
+            funding_confirms_within_blocks: options.min_funding_confirms_within_blocks,
+            channel_expiry_blocks: CHANNEL_EXPIRY_BLOCKS.min(options.max_channel_expiry_blocks),
+            refund_onchain_address: client.address().await.ok(),
+            announce_channel: true,
+        })
+        .await?;
+    let bolt11 = order.bolt11();
+    let stored = db::get()
+        .insert_lsp_order(
+            account_name,
+            &db::NewLspOrder {
+                order_id: order.order_id.clone(),
+                lsp_url: policy.lsp_url.clone(),
+                lsp_balance_sat: order.lsp_balance_sat as i64,
+                fee_total_sat: bolt11.map_or(0, |bolt11| bolt11.fee_total_sat as i64),
+                order_total_sat: bolt11.map_or(0, |bolt11| bolt11.order_total_sat as i64),
+                invoice: bolt11.map(|bolt11| bolt11.invoice.clone()),
+                order_state: order.order_state.clone(),
+                payment_state: bolt11.map(|bolt11| bolt11.state.clone()),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    println!(
+        "Placed LSP order {} for {} sats of inbound liquidity for {}",
+        stored.order_id, stored.lsp_balance_sat, account_name
+    );
+
+    let Some(bolt11) = bolt11
+        .filter(|bolt11| policy.max_fee_sat > 0 && bolt11.order_total_sat <= policy.max_fee_sat)
+    else {
+        return Ok(stored);
+    };
+    if let Err(e) = client.send_payment(&bolt11.invoice).await {
+        let error = format!("Failed to pay the order: {}", e);
+        println!(
+            "LSP order {} of {}: {}",
+            stored.order_id, account_name, error
+        );
+        // Nothing pays an unpaid order later, so it is closed here instead of
+        // staying open and blocking the next automatic order. The cooldown
+        // still applies from its creation.
+        db::get()
+            .update_lsp_order_state(
+                account_name,
+                &stored.order_id,
+                "FAILED",
+                stored.payment_state.as_deref(),
+                None,
+            )
+            .and_then(|_| db::get().set_lsp_order_error(account_name, &stored.order_id, &error))
+            .map_err(|e| e.to_string())?;
+        return Ok(db::LspOrder {
+            order_state: "FAILED".to_string(),
+            error: Some(error),
+            ..stored
+        });
+    }
+    Ok(stored)
+}
+
+/// Refreshes the open orders of an account from their LSP, returning the
+/// ones whose state changed.
+async fn refresh_orders(account_name: &str) -> Result<Vec<db::LspOrder>, String> {
+    let orders = db::get()
+        .get_lsp_orders(account_name, true)
+        .map_err(|e| e.to_string())?;
+    let mut changed = Vec::new();
+    for order in orders {
+        let latest: Result<Order, String> = match LspClient::new(&order.lsp_url) {
+            Ok(lsp) => lsp.get_order(&order.order_id).await,
+            Err(e) => Err(e),
+        };
+        let latest = match latest {
+            Ok(latest) => latest,
+            Err(e) => {
+                db::get()
+                    .set_lsp_order_error(account_name, &order.order_id, &e)
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+        };
+        let payment_state = latest.bolt11().map(|bolt11| bolt11.state.clone());
+        let funding_outpoint = latest
+            .channel
+            .as_ref()
+            .and_then(|channel| channel.funding_outpoint.clone());
+        if latest.order_state == order.order_state
+            && payment_state == order.payment_state
+            && (funding_outpoint.is_none() || funding_outpoint == order.funding_outpoint)
+        {
+            continue;
+        }
+        db::get()
+            .update_lsp_order_state(
+                account_name,
+                &order.order_id,
+                &latest.order_state,
+                payment_state.as_deref(),
+                funding_outpoint.as_deref(),
+            )
+            .map_err(|e| e.to_string())?;
+        changed.push(db::LspOrder {
+            order_state: latest.order_state,
+            payment_state,
+            funding_outpoint: funding_outpoint.or(order.funding_outpoint),
+            error: None,
+            ..order
+        });
+    }
+    Ok(changed)
+}
+
+/// Starts the background loop that watches the channel liquidity of the
+/// running node's account, alerts on the `liquidity_*` thresholds and places
+/// LSP orders when `lsp_auto_order` is on.
+pub fn spawn_monitor(app_handle: AppHandle, node_process: Arc<Mutex<NodeProcess>>) {
+    tauri::async_runtime::spawn(async move {
+        let mut low: Option<(String, LowLiquidity)> = None;
+        loop {
+            let Some(node) = background::next_tick(&node_process, CHECK_INTERVAL).await else {
+                low = None;
+                continue;
+            };
+            let account_name = node.account_name.clone();
+
+            match refresh_orders(&account_name).await {
+                Ok(changed) => {
+                    for order in changed {
+                        let _ = app_handle.emit(
+                            LSP_ORDER_EVENT,
+                            LspOrderEvent {
+                                account_name: account_name.clone(),
+                                order,
+                            },
+                        );
+                    }
+                }
+                Err(e) => println!("Failed to refresh LSP orders of {}: {}", account_name, e),
+            }
+
+            let thresholds = match load_thresholds(&account_name) {
+                Ok(thresholds) => thresholds,
+                Err(e) => {
+                    println!(
+                        "Failed to load liquidity settings of {}: {}",
+                        account_name, e
+                    );
+                    continue;
+                }
+            };
+            let policy = match load_order_policy(&account_name) {
+                Ok(policy) => policy,
+                Err(e) => {
+                    println!(
+                        "Failed to load the LSP order policy of {}: {}",
+                        account_name, e
+                    );
+                    continue;
+                }
+            };
+            if !thresholds.alerts && policy.is_none() {
+                low = None;
+                continue;
+            }
+            let Some(client) = node.unlocked_client().await else {
+                continue;
+            };
+            let Ok(channels) = client.list_channels().await else {
+                continue;
+            };
+
+            if thresholds.alerts {
+                let current = low_liquidity(&account_name, &channels, &thresholds);
+                let previous = match low.take() {
+                    Some((account, previous)) if account == account_name => previous,
+                    _ => LowLiquidity::new(),
+                };
+                for alert in crossings(&previous, &current, &channels) {
+                    let _ = app_handle.emit(LIQUIDITY_ALERT_EVENT, alert);
+                }
+                low = Some((account_name.clone(), current));
+            } else {
+                low = None;
+            }
+
+            let Some(policy) = policy else {
+                continue;
+            };
+            if total_inbound_sat(&channels) >= thresholds.min_inbound_sat {
+                continue;
+            }
+            match db::get().get_lsp_orders(&account_name, false) {
+                Ok(orders) if may_order(&orders, now_secs()) => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    println!("Failed to load LSP orders of {}: {}", account_name, e);
+                    continue;
+                }
+            }
+            match place_order(&client, &account_name, &policy).await {
+                Ok(order) => {
+                    let _ = app_handle.emit(
+                        LSP_ORDER_EVENT,
+                        LspOrderEvent {
+                            account_name,
+                            order,
+                        },
+                    );
+                }
+                Err(e) => println!("Automatic LSP order for {} failed: {}", account_name, e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(
+        id: &str,
+        outbound_sat: u64,
+        inbound_sat: u64,
+        asset: Option<(u64, u64)>,
+    ) -> Channel {
+        Channel {
+            channel_id: id.to_string(),
+            funding_txid: None,
+            peer_pubkey: "02aa".to_string(),
+            status: "Opened".to_string(),
+            ready: true,
+            capacity_sat: outbound_sat + inbound_sat,
+            local_balance_sat: outbound_sat,
+            outbound_balance_msat: outbound_sat * 1000,
+            inbound_balance_msat: inbound_sat * 1000,
+            is_usable: true,
+            public: false,
+            asset_id: asset.map(|_| "rgb:abc".to_string()),
+            asset_local_amount: asset.map(|(local, _)| local),
+            asset_remote_amount: asset.map(|(_, remote)| remote),
+        }
+    }
+
+    fn thresholds() -> Thresholds {
+        Thresholds {
+            alerts: true,
+            min_inbound_sat: 50_000,
+            min_outbound_sat: 0,
+            min_asset_percent: 10,
+        }
+    }
+
+    #[test]
+    fn alerts_when_crossing_thresholds() {
+        let before = vec![
+            channel("a", 10_000, 100_000, None),
+            channel("b", 100_000, 100_000, Some((95, 5))),
+        ];
+        let previous = low_liquidity("alice", &before, &thresholds());
+        assert_eq!(previous.len(), 1);
+        assert!(previous.contains_key(&("b".to_string(), LiquidityKind::AssetInbound)));
+
+        let after = vec![
+            channel("a", 100_000, 10_000, None),
+            channel("b", 100_000, 100_000, Some((50, 50))),
+        ];
+        let current = low_liquidity("alice", &after, &thresholds());
+        let alerts = crossings(&previous, &current, &after);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(
+            (alerts[0].kind, alerts[0].available, alerts[0].low),
+            (LiquidityKind::Inbound, 10_000, true)
+        );
+        assert_eq!(
+            (alerts[1].kind, alerts[1].available, alerts[1].low),
+            (LiquidityKind::AssetInbound, 50, false)
+        );
+        assert!(crossings(&current, &current, &after).is_empty());
+        // No recovery is reported for channels that closed
+        assert!(crossings(&current, &LowLiquidity::new(), &[]).is_empty());
+    }
+
+    #[test]
+    fn orders_only_without_open_or_recent_orders() {
+        let order = |order_state: &str, created_at: i64| db::LspOrder {
+            id: 1,
+            order_id: "order".to_string(),
+            lsp_url: "https://lsp.example.com".to_string(),
+            lsp_balance_sat: 1_000_000,
+            fee_total_sat: 1_000,
+            order_total_sat: 1_000,
+            invoice: None,
+            order_state: order_state.to_string(),
+            payment_state: None,
+            funding_outpoint: None,
+            error: None,
+            created_at,
+            updated_at: created_at,
+        };
+        let now = 1_000_000;
+        assert!(may_order(&[], now));
+        assert!(!may_order(&[order("CREATED", 0)], now));
+        assert!(!may_order(&[order("FAILED", now - 60)], now));
+        assert!(may_order(
+            &[order("COMPLETED", now - ORDER_COOLDOWN_SECS)],
+            now
+        ));
+        assert_eq!(
+            total_inbound_sat(&[channel("a", 0, 20_000, None), channel("b", 0, 30_000, None)]),
+            50_000
+        );
+    }
+}
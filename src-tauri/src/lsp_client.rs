@@ -0,0 +1,239 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct LspOptions {
+    pub min_required_channel_confirmations: u32,
+    pub min_funding_confirms_within_blocks: u32,
+    pub max_channel_expiry_blocks: u32,
+    pub min_initial_lsp_balance_sat: u64,
+    pub max_initial_lsp_balance_sat: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct LspInfo {
+    /// `pubkey@host:port` of the LSP's node.
+    pub lsp_connection_url: Option<String>,
+    pub options: LspOptions,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CreateOrderRequest {
+    pub client_pubkey: String,
+    pub lsp_balance_sat: u64,
+    pub client_balance_sat: u64,
+    pub required_channel_confirmations: u32,
+    pub funding_confirms_within_blocks: u32,
+    pub channel_expiry_blocks: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_onchain_address: Option<String>,
+    pub announce_channel: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Bolt11Payment {
+    pub state: String,
+    pub fee_total_sat: u64,
+    pub order_total_sat: u64,
+    pub invoice: String,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct OrderPayment {
+    pub bolt11: Option<Bolt11Payment>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct OrderChannel {
+    pub funding_outpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Order {
+    pub order_id: String,
+    pub lsp_balance_sat: u64,
+    /// `CREATED`, `COMPLETED` or `FAILED`.
+    pub order_state: String,
+    pub payment: Option<OrderPayment>,
+    pub channel: Option<OrderChannel>,
+}
+
+impl Order {
+    pub fn bolt11(&self) -> Option<&Bolt11Payment> {
+        self.payment.as_ref()?.bolt11.as_ref()
+    }
+}
+
+/// Client for the LSPS1 API of a Lightning Service Provider.
+pub struct LspClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl LspClient {
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(LspClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        })
+    }
+
+    async fn send<R: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<R, String> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach the LSP: {}", e))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!("The LSP answered {}: {}", status, text));
+        }
+        serde_json::from_str(&text).map_err(|e| format!("Unexpected LSP response: {}", e))
+    }
+
+    pub async fn get_info(&self) -> Result<LspInfo, String> {
+        let url = format!("{}/api/v1/lsps1/get_info", self.base_url);
+        self.send(self.http.get(url)).await
+    }
+
+    pub async fn create_order(&self, request: &CreateOrderRequest) -> Result<Order, String> {
+        let url = format!("{}/api/v1/lsps1/create_order", self.base_url);
+        self.send(self.http.post(url).json(request)).await
+    }
+
+    pub async fn get_order(&self, order_id: &str) -> Result<Order, String> {
+        let url = format!("{}/api/v1/lsps1/get_order", self.base_url);
+        self.send(
+            self.http
+                .post(url)
+                .json(&serde_json::json!({ "order_id": order_id })),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn order_json(order_state: &str) -> serde_json::Value {
+        json!({
+            "order_id": "order-1",
+            "lsp_balance_sat": 100000,
+            "order_state": order_state,
+            "payment": {
+                "bolt11": {
+                    "state": "EXPECT_PAYMENT",
+                    "fee_total_sat": 1000,
+                    "order_total_sat": 1000,
+                    "invoice": "lnbcrt10u1invoice"
+                }
+            },
+            "channel": null
+        })
+    }
+
+    #[tokio::test]
+    async fn decodes_info() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/lsps1/get_info"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "lsp_connection_url": "02abc@127.0.0.1:9735",
+                "options": {
+                    "min_required_channel_confirmations": 0,
+                    "min_funding_confirms_within_blocks": 6,
+                    "max_channel_expiry_blocks": 20000,
+                    "min_initial_lsp_balance_sat": 10000,
+                    "max_initial_lsp_balance_sat": 1000000
+                }
+            })))
+            .mount(&server)
+            .await;
+        // A trailing slash in the configured URL is ignored
+        let lsp = LspClient::new(&format!("{}/", server.uri())).unwrap();
+
+        let info = lsp.get_info().await.unwrap();
+        assert_eq!(
+            info.lsp_connection_url.as_deref(),
+            Some("02abc@127.0.0.1:9735")
+        );
+        assert_eq!(info.options.min_initial_lsp_balance_sat, 10_000);
+        assert_eq!(info.options.max_channel_expiry_blocks, 20_000);
+    }
+
+    #[tokio::test]
+    async fn creates_and_fetches_orders() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/lsps1/create_order"))
+            .and(body_json(json!({
+                "client_pubkey": "03def",
+                "lsp_balance_sat": 100000,
+                "client_balance_sat": 0,
+                "required_channel_confirmations": 0,
+                "funding_confirms_within_blocks": 6,
+                "channel_expiry_blocks": 20000,
+                "announce_channel": true
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_json("CREATED")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/lsps1/get_order"))
+            .and(body_json(json!({ "order_id": "order-1" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_json("COMPLETED")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let lsp = LspClient::new(&server.uri()).unwrap();
+
+        let order = lsp
+            .create_order(&CreateOrderRequest {
+                client_pubkey: "03def".to_string(),
+                lsp_balance_sat: 100_000,
+                client_balance_sat: 0,
+                required_channel_confirmations: 0,
+                funding_confirms_within_blocks: 6,
+                channel_expiry_blocks: 20_000,
+                refund_onchain_address: None,
+                announce_channel: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(order.order_state, "CREATED");
+        assert_eq!(order.bolt11().unwrap().invoice, "lnbcrt10u1invoice");
+
+        let order = lsp.get_order("order-1").await.unwrap();
+        assert_eq!(order.order_state, "COMPLETED");
+    }
+
+    #[tokio::test]
+    async fn reports_error_responses() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/lsps1/get_order"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("Order not found"))
+            .mount(&server)
+            .await;
+        let lsp = LspClient::new(&server.uri()).unwrap();
+
+        let error = lsp.get_order("missing").await.unwrap_err();
+        assert!(error.contains("404"), "{}", error);
+        assert!(error.contains("Order not found"), "{}", error);
+    }
+}
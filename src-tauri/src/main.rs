@@ -1,20 +1,55 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use db::Account;
+use account_archive::AccountArchive;
+use account_settings::{AccountSetting, SettingDefinition};
+use app_paths::AppPaths;
+use db::{Account, AccountAuditEntry, AccountHistory, Maker, NetworkPreset, PortCheck};
+use db_backup::VerifyReport;
 use dotenv::dotenv;
+use launch::LaunchState;
+use network_presets::PresetValues;
+use node_backup::{BackupInfo, BackupScheduler, BackupStatus};
+use node_unlock::AutoUnlockState;
+use ports::PortPair;
+use profile::{ImportPreview, ImportResolution};
 use rgb_node::NodeProcess;
+use secret_store::{SecretStore, SecretStoreStatus};
 use std::env;
 use std::sync::{Arc, Mutex, RwLock};
 use tauri::{Emitter, Manager, Window};
 
+mod account_archive;
+mod account_settings;
+mod app_paths;
+mod background;
+mod clock;
 mod db;
+mod db_backup;
+mod history;
+mod history_export;
+mod labels;
+mod launch;
+mod liquidity_monitor;
+mod lsp_client;
+mod network_presets;
+mod node_backup;
+mod node_client;
+mod node_notifications;
+mod node_unlock;
+mod peer_connect;
+mod ports;
+mod profile;
 mod rgb_node;
+mod secret_store;
+mod utxo_maintenance;
+mod wallet_sync;
 
 #[derive(Default)]
 struct CurrentAccount(RwLock<Option<Account>>);
 
 fn main() {
     dotenv().ok();
+    app_paths::init().expect("Failed to set up the app data directories");
 
     let node_process = Arc::new(Mutex::new(NodeProcess::new()));
 
@@ -27,6 +62,10 @@ fn main() {
         .plugin(tauri_plugin_dialog::init())
         .manage(Arc::clone(&node_process))
         .manage(CurrentAccount::default())
+        .manage(LaunchState::default())
+        .manage(SecretStore::default())
+        .manage(AutoUnlockState::default())
+        .manage(BackupScheduler::default())
         .on_window_event({
             let node_process = Arc::clone(&node_process);
             move |window, event| {
@@ -128,34 +167,116 @@ fn main() {
                     node_process.lock().unwrap().set_window(main_window);
                 }
                 db::init();
+                node_backup::spawn_scheduler(app.handle().clone(), Arc::clone(&node_process));
+                wallet_sync::spawn_scheduler(app.handle().clone(), Arc::clone(&node_process));
+                utxo_maintenance::spawn_maintainer(app.handle().clone(), Arc::clone(&node_process));
+                liquidity_monitor::spawn_monitor(app.handle().clone(), Arc::clone(&node_process));
+
+                // Restore the last selected account and optionally start its node
+                if let Some(account) = launch::restore_current_account() {
+                    *app.state::<CurrentAccount>().0.write().unwrap() = Some(account.clone());
+                    launch::auto_start_node(
+                        app.handle().clone(),
+                        Arc::clone(&node_process),
+                        account,
+                    );
+                }
                 Ok(())
             }
         })
         .invoke_handler(tauri::generate_handler![
             close_splashscreen,
+            get_app_paths,
             // DB commands
             get_accounts,
             insert_account,
             update_account,
             delete_account,
+            rename_account,
+            get_account_audit,
+            list_account_archives,
+            purge_account_archive,
             check_account_exists,
+            check_account_ports,
             set_current_account,
             get_current_account,
             get_account_by_name,
+            get_account_history,
+            get_makers,
+            insert_maker,
+            update_maker,
+            delete_maker,
+            record_maker_health,
+            get_settings_schema,
+            get_account_setting,
+            set_account_setting,
+            list_account_settings,
+            get_network_presets,
+            insert_network_preset,
+            update_network_preset,
+            delete_network_preset,
+            apply_preset,
+            backup_database,
+            verify_database,
+            restore_database,
+            list_database_backups,
+            list_backups,
+            get_backup_status,
+            backup_node_now,
+            query_history,
+            sync_history,
+            export_history,
+            record_swap_maker,
+            set_label,
+            get_label,
+            search_labels,
+            delete_label,
+            export_labels,
+            import_labels,
+            list_saved_peers,
+            save_peer,
+            delete_saved_peer,
+            get_utxo_maintenance_log,
+            get_lsp_orders,
+            // Secret store commands
+            get_secret_store_status,
+            setup_secret_store,
+            unlock_secret_store,
+            is_auto_unlock_pending,
+            lock_secret_store,
+            change_master_password,
+            set_account_secret,
+            get_account_secret,
+            delete_account_secret,
+            list_account_secrets,
+            // Profile commands
+            export_accounts,
+            preview_account_import,
+            import_accounts,
             // Node commands
             start_node,
             stop_node,
             get_node_logs,
             save_logs_to_file,
             is_node_running,
-            get_running_node_account
+            get_running_node_account,
+            get_running_node_ports
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 #[tauri::command]
-async fn close_splashscreen(window: Window) {
+async fn close_splashscreen(
+    window: Window,
+    launch_state: tauri::State<'_, LaunchState>,
+) -> Result<(), ()> {
+    // Keep the splashscreen up while launch tasks are still running
+    let start = std::time::Instant::now();
+    while launch_state.is_in_progress() && start.elapsed() < std::time::Duration::from_secs(60) {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
     // Show main window first
     window.show().unwrap();
     std::thread::sleep(std::time::Duration::from_millis(1000));
@@ -163,6 +284,7 @@ async fn close_splashscreen(window: Window) {
     if let Some(splashscreen) = window.get_webview_window("splashscreen") {
         splashscreen.close().unwrap();
     }
+    Ok(())
 }
 
 #[tauri::command]
@@ -173,7 +295,7 @@ fn start_node(
     daemon_listening_port: String,
     ldk_peer_listening_port: String,
     account_name: String,
-) -> Result<(), String> {
+) -> Result<PortPair, String> {
     println!("Received start_node command for account: {}", account_name);
     println!("Parameters:");
     println!("  Network: {}", network);
@@ -199,9 +321,9 @@ fn start_node(
         ldk_peer_listening_port,
         account_name,
     ) {
-        Ok(_) => {
+        Ok(ports) => {
             println!("Node started successfully");
-            Ok(())
+            Ok(ports)
         }
         Err(e) => {
             println!("Failed to start node: {}", e);
@@ -222,9 +344,14 @@ fn stop_node(node_process: tauri::State<'_, Arc<Mutex<NodeProcess>>>) -> Result<
     }
 }
 
+#[tauri::command]
+fn get_app_paths() -> AppPaths {
+    app_paths::get().clone()
+}
+
 #[tauri::command]
 fn get_accounts() -> Result<Vec<Account>, String> {
-    match db::get_accounts() {
+    match db::get().get_accounts() {
         Ok(configs) => Ok(configs),
         Err(e) => Err(e.to_string()),
     }
@@ -245,7 +372,7 @@ fn insert_account(
     daemon_listening_port: String,
     ldk_peer_listening_port: String,
 ) -> Result<usize, String> {
-    match db::insert_account(
+    let account = db::AccountFields {
         name,
         network,
         datapath,
@@ -258,7 +385,8 @@ fn insert_account(
         default_maker_url,
         daemon_listening_port,
         ldk_peer_listening_port,
-    ) {
+    };
+    match db::get().insert_account(&account, "insert_account") {
         Ok(num_rows) => Ok(num_rows),
         Err(e) => Err(e.to_string()),
     }
@@ -279,7 +407,7 @@ fn update_account(
     daemon_listening_port: String,
     ldk_peer_listening_port: String,
 ) -> Result<usize, String> {
-    match db::update_account(
+    let account = db::AccountFields {
         name,
         network,
         datapath,
@@ -292,7 +420,8 @@ fn update_account(
         default_maker_url,
         daemon_listening_port,
         ldk_peer_listening_port,
-    ) {
+    };
+    match db::get().update_account(&account, "update_account") {
         Ok(num_rows) => Ok(num_rows),
         Err(e) => Err(e.to_string()),
     }
@@ -301,20 +430,47 @@ fn update_account(
 #[tauri::command]
 fn delete_account(
     node_process: tauri::State<Arc<Mutex<NodeProcess>>>,
+    current_account: tauri::State<CurrentAccount>,
     name: String,
 ) -> Result<usize, String> {
     println!("Attempting to delete account: {}", name);
 
-    // Stop the node if it's running
-    let node_process = node_process.lock().unwrap();
-    if node_process.is_running() {
-        println!("Stopping node for account: {}", name);
-        node_process.stop();
+    let account = db::get()
+        .get_account_by_name(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Account not found: {}", name))?;
+
+    // Stop the node and wait for it to exit before touching its data
+    {
+        let node_process = node_process.lock().unwrap();
+        if node_process.is_running_for_account(&name) {
+            println!("Stopping node for account: {}", name);
+            node_process.shutdown();
+        }
+    }
+
+    // Archive first: if this fails the account is kept so no data goes missing
+    let data_dir = &app_paths::get().data_dir;
+    match account_archive::archive_account_data(data_dir, &account) {
+        Ok(Some(path)) => println!("Archived node data for {} at {:?}", name, path),
+        Ok(None) => {}
+        Err(e) => {
+            println!("Failed to archive node data for {}: {}", name, e);
+            return Err(format!(
+                "Failed to archive node data, account was not deleted: {}",
+                e
+            ));
+        }
     }
 
-    match db::delete_account(name.clone()) {
+    match db::get().delete_account(name.clone(), "delete_account") {
         Ok(num_rows) => {
             println!("Successfully deleted account: {}", name);
+            let mut current = current_account.0.write().unwrap();
+            if current.as_ref().is_some_and(|account| account.name == name) {
+                *current = None;
+                let _ = db::get().delete_app_state(launch::CURRENT_ACCOUNT_KEY);
+            }
             Ok(num_rows)
         }
         Err(e) => {
@@ -324,25 +480,91 @@ fn delete_account(
     }
 }
 
+#[tauri::command]
+fn rename_account(
+    node_process: tauri::State<Arc<Mutex<NodeProcess>>>,
+    current_account: tauri::State<CurrentAccount>,
+    name: String,
+    new_name: String,
+) -> Result<Account, String> {
+    if node_process.lock().unwrap().is_running_for_account(&name) {
+        return Err("Stop the node before renaming its account".to_string());
+    }
+
+    let account = db::get()
+        .rename_account(&name, &new_name, "rename_account")
+        .map_err(|e| e.to_string())?;
+
+    let mut current = current_account.0.write().unwrap();
+    if current.as_ref().is_some_and(|current| current.name == name) {
+        *current = Some(account.clone());
+        if let Err(e) = db::get().set_app_state(launch::CURRENT_ACCOUNT_KEY, &account.name) {
+            println!("Failed to persist current account: {}", e);
+        }
+    }
+    Ok(account)
+}
+
+#[tauri::command]
+fn get_account_audit(
+    account_name: String,
+    limit: Option<u32>,
+) -> Result<Vec<AccountAuditEntry>, String> {
+    db::get()
+        .get_account_audit(&account_name, limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn list_account_archives() -> Result<Vec<AccountArchive>, String> {
+    let data_dir = &app_paths::get().data_dir;
+    account_archive::list_archives(data_dir)
+}
+
+#[tauri::command]
+fn purge_account_archive(archive_id: String, confirmation: String) -> Result<(), String> {
+    let data_dir = &app_paths::get().data_dir;
+    account_archive::purge_archive(data_dir, &archive_id, &confirmation)
+}
+
 #[tauri::command]
 fn check_account_exists(name: String) -> Result<bool, String> {
-    match db::check_account_exists(&name) {
+    match db::get().check_account_exists(&name) {
         Ok(exists) => Ok(exists),
         Err(e) => Err(e.to_string()),
     }
 }
 
+#[tauri::command]
+fn check_account_ports(
+    name: Option<String>,
+    daemon_listening_port: String,
+    ldk_peer_listening_port: String,
+) -> Result<PortCheck, String> {
+    db::get()
+        .check_account_ports(
+            name.as_deref(),
+            &daemon_listening_port,
+            &ldk_peer_listening_port,
+        )
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn set_current_account(
     state: tauri::State<CurrentAccount>,
     account_name: String,
 ) -> Result<Account, String> {
-    let accounts = db::get_accounts().map_err(|e| e.to_string())?;
+    let accounts = db::get().get_accounts().map_err(|e| e.to_string())?;
     let account = accounts
         .into_iter()
         .find(|a| a.name == account_name)
         .ok_or_else(|| "Account not found".to_string())?;
 
+    if let Err(e) = db::get().set_app_state(launch::CURRENT_ACCOUNT_KEY, &account.name) {
+        println!("Failed to persist current account: {}", e);
+    }
+
     *state.0.write().unwrap() = Some(account.clone());
     Ok(account)
 }
@@ -354,12 +576,529 @@ fn get_current_account(state: tauri::State<CurrentAccount>) -> Option<Account> {
 
 #[tauri::command]
 fn get_account_by_name(name: String) -> Result<Option<Account>, String> {
-    match db::get_account_by_name(&name) {
+    match db::get().get_account_by_name(&name) {
         Ok(account) => Ok(account),
         Err(e) => Err(e.to_string()),
     }
 }
 
+#[tauri::command]
+fn get_account_history(account_name: String, limit: Option<u32>) -> Result<AccountHistory, String> {
+    db::get()
+        .get_account_history(&account_name, limit.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_network_presets(network: Option<String>) -> Result<Vec<NetworkPreset>, String> {
+    db::get()
+        .get_network_presets(network.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn insert_network_preset(
+    name: String,
+    network: String,
+    values: PresetValues,
+) -> Result<i64, String> {
+    network_presets::validate(&name, &network, &values)?;
+    db::get()
+        .insert_network_preset(&name, &network, &values)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_network_preset(id: i64, values: PresetValues) -> Result<usize, String> {
+    let preset = db::get()
+        .get_network_presets(None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|preset| preset.id == id)
+        .ok_or_else(|| format!("Preset not found: {}", id))?;
+    network_presets::validate(&preset.name, &preset.network, &values)?;
+    db::get()
+        .update_network_preset(id, &values)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_network_preset(id: i64) -> Result<usize, String> {
+    db::get()
+        .delete_network_preset(id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn apply_preset(
+    current_account: tauri::State<CurrentAccount>,
+    account_name: String,
+    preset_id: i64,
+) -> Result<Account, String> {
+    let account = db::get()
+        .apply_preset(&account_name, preset_id)
+        .map_err(|e| e.to_string())?;
+
+    let mut current = current_account.0.write().unwrap();
+    if current
+        .as_ref()
+        .is_some_and(|current| current.name == account.name)
+    {
+        *current = Some(account.clone());
+    }
+    Ok(account)
+}
+
+#[tauri::command]
+fn backup_database(dest_path: String) -> Result<(), String> {
+    db_backup::backup_database(&dest_path)
+}
+
+#[tauri::command]
+fn verify_database(path: Option<String>) -> Result<VerifyReport, String> {
+    db_backup::verify_database(path.as_deref())
+}
+
+#[tauri::command]
+fn restore_database(
+    app_handle: tauri::AppHandle,
+    node_process: tauri::State<Arc<Mutex<NodeProcess>>>,
+    current_account: tauri::State<CurrentAccount>,
+    secret_store: tauri::State<SecretStore>,
+    source_path: String,
+) -> Result<VerifyReport, String> {
+    if node_process.lock().unwrap().is_running() {
+        return Err("Stop the node before restoring the database".to_string());
+    }
+
+    let report = db_backup::restore_database(&source_path)?;
+
+    // The restored copy may not contain the selected account anymore
+    *current_account.0.write().unwrap() = launch::restore_current_account();
+    // Its secret store may use another password, or not be set up at all
+    secret_store.lock();
+    emit_secret_store_status(&app_handle, &secret_store);
+    Ok(report)
+}
+
+#[tauri::command]
+fn list_database_backups() -> Result<Vec<String>, String> {
+    db_backup::list_rolling_backups()
+}
+
+fn emit_secret_store_status(app_handle: &tauri::AppHandle, secret_store: &SecretStore) {
+    if let Ok(status) = secret_store.status(db::get()) {
+        let _ = app_handle.emit(secret_store::SECRET_STORE_EVENT, status);
+    }
+}
+
+#[tauri::command]
+fn get_secret_store_status(
+    secret_store: tauri::State<SecretStore>,
+) -> Result<SecretStoreStatus, String> {
+    secret_store.status(db::get())
+}
+
+// Key derivation takes a noticeable moment, so it runs on the blocking pool
+// instead of an async worker.
+async fn with_secret_store<T: Send + 'static>(
+    app_handle: &tauri::AppHandle,
+    action: impl FnOnce(&SecretStore) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || action(&app_handle.state::<SecretStore>()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn setup_secret_store(app_handle: tauri::AppHandle, password: String) -> Result<(), String> {
+    with_secret_store(&app_handle, move |store| store.setup(db::get(), &password)).await?;
+    emit_secret_store_status(&app_handle, &app_handle.state::<SecretStore>());
+    Ok(())
+}
+
+#[tauri::command]
+async fn unlock_secret_store(app_handle: tauri::AppHandle, password: String) -> Result<(), String> {
+    with_secret_store(&app_handle, move |store| store.unlock(db::get(), &password)).await?;
+    emit_secret_store_status(&app_handle, &app_handle.state::<SecretStore>());
+    Ok(())
+}
+
+#[tauri::command]
+fn is_auto_unlock_pending(
+    auto_unlock: tauri::State<AutoUnlockState>,
+    account_name: String,
+) -> bool {
+    auto_unlock.is_pending(&account_name)
+}
+
+#[tauri::command]
+fn lock_secret_store(app_handle: tauri::AppHandle, secret_store: tauri::State<SecretStore>) {
+    secret_store.lock();
+    emit_secret_store_status(&app_handle, &secret_store);
+}
+
+#[tauri::command]
+async fn change_master_password(
+    app_handle: tauri::AppHandle,
+    old_password: String,
+    new_password: String,
+) -> Result<(), String> {
+    with_secret_store(&app_handle, move |store| {
+        store.change_password(db::get(), &old_password, &new_password)
+    })
+    .await
+}
+
+#[tauri::command]
+fn set_account_secret(
+    secret_store: tauri::State<SecretStore>,
+    account_name: String,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    secret_store.set(db::get(), &account_name, &key, &value)
+}
+
+#[tauri::command]
+fn get_account_secret(
+    secret_store: tauri::State<SecretStore>,
+    account_name: String,
+    key: String,
+) -> Result<Option<String>, String> {
+    secret_store.get(db::get(), &account_name, &key)
+}
+
+#[tauri::command]
+fn delete_account_secret(
+    secret_store: tauri::State<SecretStore>,
+    account_name: String,
+    key: String,
+) -> Result<usize, String> {
+    secret_store.delete(db::get(), &account_name, &key)
+}
+
+#[tauri::command]
+fn list_account_secrets(
+    secret_store: tauri::State<SecretStore>,
+    account_name: String,
+) -> Result<Vec<String>, String> {
+    secret_store.list(db::get(), &account_name)
+}
+
+#[tauri::command]
+fn list_backups(account_name: String) -> Result<Vec<BackupInfo>, String> {
+    node_backup::list_backups(&account_name)
+}
+
+#[tauri::command]
+fn get_backup_status(
+    backup_scheduler: tauri::State<BackupScheduler>,
+    account_name: String,
+) -> Result<BackupStatus, String> {
+    backup_scheduler.status(&account_name)
+}
+
+#[tauri::command]
+async fn backup_node_now(
+    app_handle: tauri::AppHandle,
+    node_process: tauri::State<'_, Arc<Mutex<NodeProcess>>>,
+    backup_scheduler: tauri::State<'_, BackupScheduler>,
+    account_name: String,
+) -> Result<db::NodeBackup, String> {
+    let ports = {
+        let node_process = node_process.lock().unwrap();
+        if !node_process.is_running_for_account(&account_name) {
+            return Err(format!("The node of {} is not running", account_name));
+        }
+        node_process
+            .get_current_ports()
+            .ok_or_else(|| "The node is not running".to_string())?
+    };
+    backup_scheduler
+        .run(
+            &app_handle,
+            &account_name,
+            &ports.daemon_listening_port,
+            "manual",
+        )
+        .await
+}
+
+#[tauri::command]
+fn query_history(account_name: String, query: db::HistoryQuery) -> Result<db::HistoryPage, String> {
+    db::get()
+        .query_history(&account_name, &query)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn sync_history(
+    app_handle: tauri::AppHandle,
+    node_process: tauri::State<'_, Arc<Mutex<NodeProcess>>>,
+    account_name: String,
+) -> Result<usize, String> {
+    let ports = {
+        let node_process = node_process.lock().unwrap();
+        if !node_process.is_running_for_account(&account_name) {
+            return Err(format!("The node of {} is not running", account_name));
+        }
+        node_process
+            .get_current_ports()
+            .ok_or_else(|| "The node is not running".to_string())?
+    };
+    let client =
+        node_client::NodeClient::local(&ports.daemon_listening_port).map_err(|e| e.to_string())?;
+    history::sync(&app_handle, &client, &account_name).await
+}
+
+#[tauri::command]
+fn export_history(
+    account_names: Vec<String>,
+    dest_path: String,
+    format: history_export::ExportFormat,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<usize, String> {
+    history_export::export_history(
+        &account_names,
+        std::path::Path::new(&dest_path),
+        format,
+        from,
+        to,
+    )
+}
+
+/// Remembers which maker executed a swap of the current account, for exports.
+#[tauri::command]
+fn record_swap_maker(
+    current_account: tauri::State<CurrentAccount>,
+    payment_hash: String,
+    maker_url: String,
+) -> Result<(), String> {
+    let account = current_account
+        .0
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "No account selected".to_string())?;
+    db::get()
+        .record_swap_maker(&account.name, &payment_hash, &maker_url)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Stores a label, or removes it when both the text and the tags are empty.
+#[tauri::command]
+fn set_label(account_name: String, label: db::Label) -> Result<(), String> {
+    let label = labels::normalize(label)?;
+    let result = if label.label.is_empty() && label.tags.is_empty() {
+        db::get().delete_label(&account_name, &label.label_type, &label.reference)
+    } else {
+        db::get().set_label(&account_name, &label)
+    };
+    result.map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_label(
+    account_name: String,
+    label_type: String,
+    reference: String,
+) -> Result<Option<db::Label>, String> {
+    db::get()
+        .get_label(&account_name, &label_type, &reference)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn search_labels(
+    account_name: String,
+    search: Option<String>,
+    label_type: Option<String>,
+) -> Result<Vec<db::Label>, String> {
+    db::get()
+        .search_labels(&account_name, search.as_deref(), label_type.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_label(account_name: String, label_type: String, reference: String) -> Result<(), String> {
+    db::get()
+        .delete_label(&account_name, &label_type, &reference)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_labels(account_name: String, dest_path: String) -> Result<usize, String> {
+    labels::export_labels(&account_name, std::path::Path::new(&dest_path))
+}
+
+#[tauri::command]
+fn import_labels(
+    account_name: String,
+    src_path: String,
+) -> Result<labels::LabelImportReport, String> {
+    labels::import_labels(&account_name, std::path::Path::new(&src_path))
+}
+
+#[tauri::command]
+fn list_saved_peers(account_name: String) -> Result<Vec<db::SavedPeer>, String> {
+    db::get()
+        .get_saved_peers(&account_name)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_peer(
+    account_name: String,
+    pubkey: String,
+    address: String,
+    alias: Option<String>,
+    auto_connect: Option<bool>,
+) -> Result<i64, String> {
+    let pubkey = pubkey.trim().to_lowercase();
+    let address = address.trim();
+    peer_connect::validate_peer(&pubkey, address)?;
+    let alias = alias
+        .as_deref()
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty());
+    db::get()
+        .save_peer(
+            &account_name,
+            &pubkey,
+            address,
+            alias,
+            auto_connect.unwrap_or(true),
+        )
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_saved_peer(account_name: String, pubkey: String) -> Result<(), String> {
+    db::get()
+        .delete_saved_peer(&account_name, &pubkey.trim().to_lowercase())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_utxo_maintenance_log(
+    account_name: String,
+    limit: Option<u32>,
+) -> Result<Vec<db::UtxoMaintenanceEntry>, String> {
+    db::get()
+        .get_utxo_maintenance_log(&account_name, limit.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_lsp_orders(account_name: String, open_only: bool) -> Result<Vec<db::LspOrder>, String> {
+    db::get()
+        .get_lsp_orders(&account_name, open_only)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_makers(account_name: String) -> Result<Vec<Maker>, String> {
+    db::get()
+        .get_makers(&account_name)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn insert_maker(
+    account_name: String,
+    url: String,
+    label: Option<String>,
+    enabled: Option<bool>,
+    priority: Option<i32>,
+) -> Result<i64, String> {
+    db::get()
+        .insert_maker(
+            &account_name,
+            url,
+            label.unwrap_or_default(),
+            enabled.unwrap_or(true),
+            priority.unwrap_or(0),
+        )
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_maker(
+    id: i64,
+    url: String,
+    label: String,
+    enabled: bool,
+    priority: i32,
+) -> Result<usize, String> {
+    db::get()
+        .update_maker(id, url, label, enabled, priority)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_maker(id: i64) -> Result<usize, String> {
+    db::get().delete_maker(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn record_maker_health(id: i64, latency_ms: Option<i64>) -> Result<usize, String> {
+    db::get()
+        .record_maker_health(id, latency_ms)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_settings_schema() -> Vec<SettingDefinition> {
+    account_settings::schema()
+}
+
+#[tauri::command]
+fn get_account_setting(account_name: String, key: String) -> Result<serde_json::Value, String> {
+    account_settings::get(&account_name, &key)
+}
+
+#[tauri::command]
+fn set_account_setting(
+    app_handle: tauri::AppHandle,
+    account_name: String,
+    key: String,
+    value: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let event = account_settings::set(&account_name, &key, value)?;
+    let value = event.value.clone();
+    let _ = app_handle.emit(account_settings::SETTING_CHANGED_EVENT, event);
+    Ok(value)
+}
+
+#[tauri::command]
+fn list_account_settings(account_name: String) -> Result<Vec<AccountSetting>, String> {
+    account_settings::list(&account_name)
+}
+
+#[tauri::command]
+fn export_accounts(file_path: String, names: Option<Vec<String>>) -> Result<usize, String> {
+    profile::export_accounts(&file_path, names)
+}
+
+#[tauri::command]
+fn preview_account_import(file_path: String) -> Result<ImportPreview, String> {
+    profile::preview_import(&file_path)
+}
+
+#[tauri::command]
+fn import_accounts(
+    file_path: String,
+    resolutions: Option<Vec<ImportResolution>>,
+) -> Result<Vec<String>, String> {
+    profile::import_accounts(&file_path, resolutions.unwrap_or_default())
+}
+
 #[tauri::command]
 fn get_node_logs(node_process: tauri::State<'_, Arc<Mutex<NodeProcess>>>) -> Vec<String> {
     node_process.lock().unwrap().get_logs()
@@ -392,3 +1131,10 @@ fn get_running_node_account(
 ) -> Option<String> {
     node_process.lock().unwrap().get_current_account()
 }
+
+#[tauri::command]
+fn get_running_node_ports(
+    node_process: tauri::State<'_, Arc<Mutex<NodeProcess>>>,
+) -> Option<PortPair> {
+    node_process.lock().unwrap().get_current_ports()
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+/// Networks an account (and so a preset) can target.
+pub const NETWORKS: &[&str] = &["Regtest", "Signet", "Testnet", "Mainnet"];
+
+/// The service URLs a preset fills in on an account.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PresetValues {
+    pub rpc_connection_url: String,
+    pub indexer_url: String,
+    pub proxy_endpoint: String,
+    pub default_lsp_url: String,
+    pub default_maker_url: String,
+}
+
+pub struct BuiltinPreset {
+    pub name: &'static str,
+    pub network: &'static str,
+    pub values: PresetValues,
+}
+
+/// Presets shipped with the app. They are synced into the NetworkPresets table on
+/// startup, so changing a URL here updates every account still using the old one.
+pub fn builtin_presets() -> Vec<BuiltinPreset> {
+    vec![
+        BuiltinPreset {
+            name: "Kaleidoswap Regtest",
+            network: "Regtest",
+            values: PresetValues {
+                rpc_connection_url: "user:password@regtest-bitcoind.rgbtools.org:80".to_string(),
+                indexer_url: "electrum.rgbtools.org:50041".to_string(),
+                proxy_endpoint: "rpcs://proxy.iriswallet.com/0.2/json-rpc".to_string(),
+                default_lsp_url: "https://api.regtest.kaleidoswap.com/".to_string(),
+                default_maker_url: "https://api.regtest.kaleidoswap.com/".to_string(),
+            },
+        },
+        BuiltinPreset {
+            name: "Kaleidoswap Signet",
+            network: "Signet",
+            values: PresetValues {
+                rpc_connection_url: "user:default_password@bitcoind.signet.kaleidoswap.com:38332"
+                    .to_string(),
+                indexer_url: "electrum.signet.kaleidoswap.com:60601".to_string(),
+                proxy_endpoint: "rpcs://proxy.signet.kaleidoswap.com/json-rpc".to_string(),
+                default_lsp_url: "https://api.signet.kaleidoswap.com/".to_string(),
+                default_maker_url: "https://api.signet.kaleidoswap.com/".to_string(),
+            },
+        },
+        BuiltinPreset {
+            name: "Kaleidoswap Testnet",
+            network: "Testnet",
+            values: PresetValues {
+                rpc_connection_url: "user:password@electrum.iriswallet.com:18332".to_string(),
+                indexer_url: "ssl://electrum.iriswallet.com:50013".to_string(),
+                proxy_endpoint: "rpcs://proxy.iriswallet.com/0.2/json-rpc".to_string(),
+                default_lsp_url: "https://api.testnet.kaleidoswap.com/".to_string(),
+                default_maker_url: "https://api.testnet.kaleidoswap.com/".to_string(),
+            },
+        },
+        BuiltinPreset {
+            name: "Local Mainnet",
+            network: "Mainnet",
+            values: PresetValues {
+                rpc_connection_url: "user:password@127.0.0.1:8332".to_string(),
+                indexer_url: "127.0.0.1:50001".to_string(),
+                proxy_endpoint: "rpc://127.0.0.1:3000/json-rpc".to_string(),
+                default_lsp_url: "https://api.kaleidoswap.com/".to_string(),
+                default_maker_url: "https://api.kaleidoswap.com/".to_string(),
+            },
+        },
+    ]
+}
+
+/// Checks a user-defined preset before it is stored.
+pub fn validate(name: &str, network: &str, values: &PresetValues) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    if !NETWORKS.contains(&network) {
+        return Err(format!(
+            "Unknown network '{}' (expected one of {})",
+            network,
+            NETWORKS.join(", ")
+        ));
+    }
+    if values.rpc_connection_url.trim().is_empty()
+        || values.indexer_url.trim().is_empty()
+        || values.proxy_endpoint.trim().is_empty()
+    {
+        return Err("RPC, indexer and proxy URLs are required".to_string());
+    }
+    Ok(())
+}
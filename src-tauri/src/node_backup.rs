@@ -0,0 +1,541 @@
+use crate::clock::now_secs;
+use crate::db::{self, NodeBackup};
+use crate::node_client::{NodeClient, NodeClientError, Payment, Swaps};
+use crate::rgb_node::NodeProcess;
+use crate::{account_settings, background, node_unlock};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Emitted with the backup account name when the node is locked for a backup.
+pub const BACKUP_STARTED_EVENT: &str = "node-backup-started";
+/// Emitted with a `BackupFinishedEvent` once the node is unlocked again.
+pub const BACKUP_FINISHED_EVENT: &str = "node-backup-finished";
+/// Emitted with a `BackupUnlockFailedEvent` when the node stays locked after
+/// a backup and has to be unlocked by hand.
+pub const BACKUP_UNLOCK_FAILED_EVENT: &str = "node-backup-unlock-failed";
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+/// Minimum delay before a failed scheduled backup is attempted again.
+const RETRY_DELAY_SECS: i64 = 600;
+
+/// Whether a backup file still matches the checksum taken when it was written.
+/// The node encrypts the archive, so this is the only check possible without
+/// restoring it.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumState {
+    Matches,
+    Missing,
+    Mismatch,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupInfo {
+    #[serde(flatten)]
+    pub backup: NodeBackup,
+    pub checksum: ChecksumState,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupFinishedEvent {
+    pub account_name: String,
+    pub reason: String,
+    pub backup: Option<NodeBackup>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupUnlockFailedEvent {
+    pub account_name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupStatus {
+    pub account_name: String,
+    /// True when a directory is set and the interval or channel trigger is on.
+    pub enabled: bool,
+    pub directory: String,
+    pub interval_hours: i64,
+    pub on_channel_change: bool,
+    pub generations: i64,
+    pub in_progress: bool,
+    pub last_backup_at: Option<i64>,
+    pub next_backup_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_attempt_at: Option<i64>,
+}
+
+struct BackupSettings {
+    directory: String,
+    interval_hours: i64,
+    on_channel_change: bool,
+    generations: i64,
+}
+
+impl BackupSettings {
+    fn load(account_name: &str) -> Result<Self, String> {
+        Ok(BackupSettings {
+            directory: account_settings::get_string(account_name, "backup_directory")?,
+            interval_hours: account_settings::get_i64(account_name, "backup_interval_hours")?,
+            on_channel_change: account_settings::get_bool(
+                account_name,
+                "backup_on_channel_change",
+            )?,
+            generations: account_settings::get_i64(account_name, "backup_generations")?,
+        })
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.directory.trim().is_empty() && (self.interval_hours > 0 || self.on_channel_change)
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    in_progress: bool,
+    last_error: Option<(String, String)>,
+    last_attempt: Option<(String, i64)>,
+    /// Ready channels of the running node seen on the previous tick.
+    channels: Option<(String, Vec<String>)>,
+}
+
+/// Tracks the backup scheduler so its status can be reported to the frontend.
+#[derive(Default)]
+pub struct BackupScheduler {
+    state: Mutex<SchedulerState>,
+}
+
+fn sha256_file(path: &Path) -> Result<(String, i64), String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open backup {:?}: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0i64;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read backup {:?}: {}", path, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as i64;
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+fn checksum_state(backup: &NodeBackup) -> ChecksumState {
+    match sha256_file(Path::new(&backup.path)) {
+        Ok((sha256, size)) if sha256 == backup.sha256 && size == backup.size => {
+            ChecksumState::Matches
+        }
+        Ok(_) => ChecksumState::Mismatch,
+        Err(_) if !Path::new(&backup.path).exists() => ChecksumState::Missing,
+        Err(_) => ChecksumState::Mismatch,
+    }
+}
+
+fn backup_path(directory: &Path, account_name: &str, reason: &str) -> PathBuf {
+    let name: String = account_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut path = directory.join(format!("{}-{}-{}.enc", name, now_secs(), reason));
+    let mut suffix = 1;
+    while path.exists() {
+        path = directory.join(format!("{}-{}-{}-{}.enc", name, now_secs(), reason, suffix));
+        suffix += 1;
+    }
+    path
+}
+
+/// Deletes the backups of an account beyond the newest `generations`.
+fn rotate(account_name: &str, generations: i64) -> Result<(), String> {
+    let backups = db::get()
+        .get_node_backups(account_name)
+        .map_err(|e| e.to_string())?;
+    for backup in backups.into_iter().skip(generations.max(1) as usize) {
+        if let Err(e) = fs::remove_file(&backup.path) {
+            if Path::new(&backup.path).exists() {
+                println!("Failed to remove old node backup {}: {}", backup.path, e);
+                continue;
+            }
+        }
+        db::get()
+            .delete_node_backup(backup.id)
+            .map_err(|e| e.to_string())?;
+        println!("Removed old node backup {}", backup.path);
+    }
+    Ok(())
+}
+
+/// Why locking the node now would break something in progress, if it would.
+fn activity_in_flight(swaps: &Swaps, payments: &[Payment]) -> Option<&'static str> {
+    if swaps
+        .maker
+        .iter()
+        .chain(&swaps.taker)
+        .any(|swap| swap.status == "Waiting" || swap.status == "Pending")
+    {
+        return Some("a swap is in progress");
+    }
+    if payments.iter().any(|payment| payment.status == "Pending") {
+        return Some("a payment is in flight");
+    }
+    None
+}
+
+/// Checks that the node can be locked for a backup. Fails when it can't tell.
+async fn check_idle(client: &NodeClient) -> Result<(), String> {
+    let swaps = client.list_swaps().await.map_err(|e| e.to_string())?;
+    let payments = client.list_payments().await.map_err(|e| e.to_string())?;
+    match activity_in_flight(&swaps, &payments) {
+        Some(activity) => Err(format!("Backup postponed, {}", activity)),
+        None => Ok(()),
+    }
+}
+
+/// Locks the node, writes an encrypted backup with the node password and
+/// unlocks the node again. The node has to be locked while the backup runs,
+/// so API calls fail for the duration and it is refused while swaps or
+/// payments are in flight.
+///
+/// The archive is encrypted by the node, so its content can't be checked
+/// here. Its checksum is taken once it is on disk, for later comparison. A
+/// backup that was written is recorded even if unlocking fails afterwards;
+/// that failure is reported with `node-backup-unlock-failed`.
+async fn backup_node(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+    reason: &str,
+) -> Result<NodeBackup, String> {
+    let settings = BackupSettings::load(account_name)?;
+    if settings.directory.trim().is_empty() {
+        return Err("No backup directory is configured".to_string());
+    }
+    let directory = PathBuf::from(&settings.directory);
+    fs::create_dir_all(&directory)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let account = db::get()
+        .get_account_by_name(account_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Account not found: {}", account_name))?;
+    let password =
+        node_unlock::node_password(app_handle, account_name).map_err(|e| e.to_string())?;
+    let unlock_request =
+        node_unlock::unlock_request(&account, password.clone()).map_err(|e| e.to_string())?;
+
+    let path = backup_path(&directory, account_name, reason);
+    let path_str = path.to_string_lossy().to_string();
+
+    check_idle(client).await?;
+    client.lock().await.map_err(|e| e.to_string())?;
+    let _ = app_handle.emit(BACKUP_STARTED_EVENT, account_name);
+    let backup_result = client.backup(&path_str, &password).await;
+    let unlock_result = match client.unlock(&unlock_request).await {
+        Ok(()) | Err(NodeClientError::AlreadyUnlocked) => Ok(()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = unlock_result {
+        let message = format!("The node stayed locked after the backup: {}", e);
+        println!("Node backup for {}: {}", account_name, message);
+        let _ = app_handle.emit(
+            BACKUP_UNLOCK_FAILED_EVENT,
+            BackupUnlockFailedEvent {
+                account_name: account_name.to_string(),
+                message,
+            },
+        );
+    }
+    backup_result.map_err(|e| format!("Backup failed: {}", e))?;
+
+    // Checksum what is actually on disk
+    fs::File::open(&path)
+        .and_then(|file| file.sync_all())
+        .map_err(|e| format!("Failed to flush backup {:?}: {}", path, e))?;
+    let (sha256, size) = sha256_file(&path)?;
+    if size == 0 {
+        let _ = fs::remove_file(&path);
+        return Err("The node wrote an empty backup".to_string());
+    }
+    let id = db::get()
+        .insert_node_backup(account_name, &path_str, size, &sha256, reason)
+        .map_err(|e| e.to_string())?;
+    println!("Node backup ({}) written to {}", reason, path_str);
+
+    if let Err(e) = rotate(account_name, settings.generations) {
+        println!("Failed to rotate node backups: {}", e);
+    }
+
+    Ok(NodeBackup {
+        id,
+        path: path_str,
+        size,
+        sha256,
+        reason: reason.to_string(),
+        created_at: now_secs(),
+    })
+}
+
+impl BackupScheduler {
+    /// Runs a backup of the node currently running for `account_name`, records
+    /// the outcome and emits `node-backup-finished`.
+    pub async fn run(
+        &self,
+        app_handle: &AppHandle,
+        account_name: &str,
+        daemon_listening_port: &str,
+        reason: &str,
+    ) -> Result<NodeBackup, String> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.in_progress {
+                return Err("A node backup is already running".to_string());
+            }
+            state.in_progress = true;
+            state.last_attempt = Some((account_name.to_string(), now_secs()));
+        }
+
+        let result = match NodeClient::local(daemon_listening_port) {
+            Ok(client) => backup_node(app_handle, &client, account_name, reason).await,
+            Err(e) => Err(e.to_string()),
+        };
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.in_progress = false;
+            state.last_error = result
+                .as_ref()
+                .err()
+                .map(|e| (account_name.to_string(), e.clone()));
+        }
+        if let Err(e) = &result {
+            println!("Node backup for {} failed: {}", account_name, e);
+        }
+        let _ = app_handle.emit(
+            BACKUP_FINISHED_EVENT,
+            BackupFinishedEvent {
+                account_name: account_name.to_string(),
+                reason: reason.to_string(),
+                backup: result.as_ref().ok().cloned(),
+                error: result.as_ref().err().cloned(),
+            },
+        );
+        result
+    }
+
+    pub fn status(&self, account_name: &str) -> Result<BackupStatus, String> {
+        let settings = BackupSettings::load(account_name)?;
+        let last_backup_at = db::get()
+            .get_node_backups(account_name)
+            .map_err(|e| e.to_string())?
+            .first()
+            .map(|backup| backup.created_at);
+        let next_backup_at = (settings.is_enabled() && settings.interval_hours > 0).then(|| {
+            last_backup_at
+                .map(|at| at + settings.interval_hours * 3600)
+                .unwrap_or_else(now_secs)
+        });
+
+        let state = self.state.lock().unwrap();
+        Ok(BackupStatus {
+            account_name: account_name.to_string(),
+            enabled: settings.is_enabled(),
+            directory: settings.directory,
+            interval_hours: settings.interval_hours,
+            on_channel_change: settings.on_channel_change,
+            generations: settings.generations,
+            in_progress: state.in_progress,
+            last_backup_at,
+            next_backup_at,
+            last_error: state
+                .last_error
+                .as_ref()
+                .filter(|(name, _)| name == account_name)
+                .map(|(_, error)| error.clone()),
+            last_attempt_at: state
+                .last_attempt
+                .as_ref()
+                .filter(|(name, _)| name == account_name)
+                .map(|(_, at)| *at),
+        })
+    }
+
+    /// Returns the reason for a backup if one is due on this tick.
+    async fn due_reason(
+        &self,
+        client: &NodeClient,
+        account_name: &str,
+        settings: &BackupSettings,
+    ) -> Option<&'static str> {
+        // Channel changes are tracked on every tick so a change made while the
+        // interval is not due yet still triggers a backup
+        let mut channel_change = false;
+        if let Ok(channels) = client.list_channels().await {
+            let mut ready: Vec<String> = channels
+                .into_iter()
+                .filter(|channel| channel.ready)
+                .map(|channel| channel.channel_id)
+                .collect();
+            ready.sort();
+            let mut state = self.state.lock().unwrap();
+            channel_change = matches!(
+                &state.channels,
+                Some((name, previous)) if name == account_name && *previous != ready
+            );
+            state.channels = Some((account_name.to_string(), ready));
+        }
+
+        {
+            let state = self.state.lock().unwrap();
+            let failed_recently = state
+                .last_error
+                .as_ref()
+                .is_some_and(|(name, _)| name == account_name)
+                && state.last_attempt.as_ref().is_some_and(|(name, at)| {
+                    name == account_name && now_secs() - at < RETRY_DELAY_SECS
+                });
+            if state.in_progress || failed_recently {
+                return None;
+            }
+        }
+
+        if channel_change && settings.on_channel_change {
+            return Some("channel-change");
+        }
+        if settings.interval_hours > 0 {
+            let last_backup_at = db::get()
+                .get_node_backups(account_name)
+                .ok()?
+                .first()
+                .map(|backup| backup.created_at);
+            let due = last_backup_at.map(|at| now_secs() >= at + settings.interval_hours * 3600);
+            if due.unwrap_or(true) {
+                return Some("scheduled");
+            }
+        }
+        None
+    }
+}
+
+/// Lists the backups of an account, newest first, comparing each file with
+/// the checksum taken when it was written.
+pub fn list_backups(account_name: &str) -> Result<Vec<BackupInfo>, String> {
+    Ok(db::get()
+        .get_node_backups(account_name)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|backup| BackupInfo {
+            checksum: checksum_state(&backup),
+            backup,
+        })
+        .collect())
+}
+
+/// Starts the background loop backing up the running node of accounts that
+/// configured a backup directory. Backups are only taken while the node is
+/// unlocked and the secret store holds its password.
+pub fn spawn_scheduler(app_handle: AppHandle, node_process: Arc<Mutex<NodeProcess>>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let running = background::next_tick(&node_process, SCHEDULER_TICK).await;
+            let scheduler = app_handle.state::<BackupScheduler>();
+            let Some(node) = running else {
+                scheduler.state.lock().unwrap().channels = None;
+                continue;
+            };
+            let account_name = &node.account_name;
+
+            let settings = match BackupSettings::load(account_name) {
+                Ok(settings) if settings.is_enabled() => settings,
+                _ => continue,
+            };
+            // A locked node cannot be unlocked again after the backup
+            let Some(client) = node.unlocked_client().await else {
+                continue;
+            };
+
+            if let Some(reason) = scheduler.due_reason(&client, account_name, &settings).await {
+                // Try again on the next tick rather than failing the backup
+                if let Err(e) = check_idle(&client).await {
+                    println!("Node backup for {}: {}", account_name, e);
+                    continue;
+                }
+                let _ = scheduler
+                    .run(
+                        &app_handle,
+                        account_name,
+                        &node.ports.daemon_listening_port,
+                        reason,
+                    )
+                    .await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_client::Swap;
+
+    fn swap(status: &str) -> Swap {
+        Swap {
+            payment_hash: "hash".to_string(),
+            qty_from: 1,
+            qty_to: 1,
+            from_asset: None,
+            to_asset: Some("rgb:abc".to_string()),
+            status: status.to_string(),
+            requested_at: None,
+            initiated_at: None,
+            expires_at: None,
+            completed_at: None,
+        }
+    }
+
+    fn payment(status: &str) -> Payment {
+        Payment {
+            amt_msat: Some(1000),
+            asset_amount: None,
+            asset_id: None,
+            payment_hash: "hash".to_string(),
+            inbound: false,
+            payee_pubkey: None,
+            status: status.to_string(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn backups_wait_for_swaps_and_payments() {
+        let idle = Swaps {
+            maker: vec![swap("Succeeded")],
+            taker: vec![swap("Expired")],
+        };
+        assert_eq!(activity_in_flight(&idle, &[payment("Failed")]), None);
+
+        let swapping = Swaps {
+            maker: Vec::new(),
+            taker: vec![swap("Pending")],
+        };
+        assert!(activity_in_flight(&swapping, &[]).is_some());
+        assert!(activity_in_flight(&idle, &[payment("Pending")]).is_some());
+    }
+}
@@ -0,0 +1,692 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Timeout of a single request to the node.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Unlocking syncs the wallet with the indexer, which can take a while.
+const UNLOCK_TIMEOUT: Duration = Duration::from_secs(120);
+/// Backups archive and encrypt the whole node data directory.
+const BACKUP_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Error messages returned by rgb-lightning-node
+const LOCKED: &str = "Node is locked";
+const NOT_INITIALIZED: &str = "Wallet has not been initialized";
+const ALREADY_UNLOCKED: &str = "Node has already been unlocked";
+const CHANGING_STATE: &str = "Cannot call other APIs while node is changing state";
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NodeClientError {
+    /// The node could not be reached (not started yet, wrong port, ...).
+    Connection {
+        message: String,
+    },
+    Timeout,
+    Locked,
+    NotInitialized,
+    AlreadyUnlocked,
+    /// The node is busy unlocking, locking or shutting down. Worth retrying.
+    ChangingState,
+    /// Any other error response from the node.
+    Api {
+        status: u16,
+        message: String,
+    },
+    /// The response did not match the expected shape.
+    Decode {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for NodeClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeClientError::Connection { message } => {
+                write!(f, "Cannot reach the node: {}", message)
+            }
+            NodeClientError::Timeout => write!(f, "The node did not answer in time"),
+            NodeClientError::Locked => write!(f, "The node is locked"),
+            NodeClientError::NotInitialized => write!(f, "The wallet has not been initialized"),
+            NodeClientError::AlreadyUnlocked => write!(f, "The node is already unlocked"),
+            NodeClientError::ChangingState => write!(f, "The node is changing state"),
+            NodeClientError::Api { status, message } => {
+                write!(f, "Node error ({}): {}", status, message)
+            }
+            NodeClientError::Decode { message } => {
+                write!(f, "Unexpected response from the node: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NodeClientError {}
+
+impl From<reqwest::Error> for NodeClientError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            NodeClientError::Timeout
+        } else if e.is_decode() {
+            NodeClientError::Decode {
+                message: e.to_string(),
+            }
+        } else {
+            NodeClientError::Connection {
+                message: e.to_string(),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+// Requests and responses. Only the fields the app uses are declared, unknown
+// fields in responses are ignored.
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NodeInfo {
+    pub pubkey: String,
+    pub num_channels: u64,
+    pub num_usable_channels: u64,
+    pub local_balance_sat: u64,
+    pub num_peers: u64,
+    pub onchain_pubkey: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Balance {
+    pub settled: u64,
+    pub future: u64,
+    pub spendable: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BtcBalance {
+    pub vanilla: Balance,
+    pub colored: Balance,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AssetBalance {
+    pub settled: u64,
+    pub future: u64,
+    pub spendable: u64,
+    #[serde(default)]
+    pub offchain_outbound: u64,
+    #[serde(default)]
+    pub offchain_inbound: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NiaAsset {
+    pub asset_id: String,
+    pub ticker: String,
+    pub name: String,
+    pub precision: u8,
+    pub issued_supply: u64,
+    pub balance: AssetBalance,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Assets {
+    #[serde(default)]
+    pub nia: Vec<NiaAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Channel {
+    pub channel_id: String,
+    pub funding_txid: Option<String>,
+    pub peer_pubkey: String,
+    pub status: String,
+    pub ready: bool,
+    pub capacity_sat: u64,
+    pub local_balance_sat: u64,
+    pub outbound_balance_msat: u64,
+    pub inbound_balance_msat: u64,
+    pub is_usable: bool,
+    pub public: bool,
+    pub asset_id: Option<String>,
+    pub asset_local_amount: Option<u64>,
+    pub asset_remote_amount: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Peer {
+    pub pubkey: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Payment {
+    pub amt_msat: Option<u64>,
+    pub asset_amount: Option<u64>,
+    pub asset_id: Option<String>,
+    pub payment_hash: String,
+    pub inbound: bool,
+    #[serde(default)]
+    pub payee_pubkey: Option<String>,
+    pub status: String,
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub updated_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Transfer {
+    pub idx: i64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub status: String,
+    pub amount: u64,
+    pub kind: String,
+    pub txid: Option<String>,
+    pub recipient_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConfirmationTime {
+    pub height: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Transaction {
+    pub transaction_type: String,
+    pub txid: String,
+    pub received: u64,
+    pub sent: u64,
+    pub fee: u64,
+    pub confirmation_time: Option<ConfirmationTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Swap {
+    pub payment_hash: String,
+    pub qty_from: u64,
+    pub qty_to: u64,
+    pub from_asset: Option<String>,
+    pub to_asset: Option<String>,
+    pub status: String,
+    pub requested_at: Option<u64>,
+    pub initiated_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub completed_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Swaps {
+    #[serde(default)]
+    pub maker: Vec<Swap>,
+    #[serde(default)]
+    pub taker: Vec<Swap>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Utxo {
+    pub outpoint: String,
+    pub btc_amount: u64,
+    pub colorable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RgbAllocation {
+    pub asset_id: Option<String>,
+    pub amount: u64,
+    pub settled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Unspent {
+    pub utxo: Utxo,
+    #[serde(default)]
+    pub rgb_allocations: Vec<RgbAllocation>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct UnlockRequest {
+    pub password: String,
+    pub bitcoind_rpc_username: String,
+    pub bitcoind_rpc_password: String,
+    pub bitcoind_rpc_host: String,
+    pub bitcoind_rpc_port: u16,
+    pub indexer_url: String,
+    pub proxy_endpoint: String,
+    pub announce_addresses: Vec<String>,
+    pub announce_alias: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SendPaymentResponse {
+    pub payment_hash: String,
+    pub payment_secret: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CreateUtxosRequest {
+    pub up_to: bool,
+    pub num: Option<u8>,
+    pub size: Option<u32>,
+    pub fee_rate: u64,
+    pub skip_sync: bool,
+}
+
+#[derive(Deserialize)]
+struct ChannelList {
+    channels: Vec<Channel>,
+}
+
+#[derive(Deserialize)]
+struct PeerList {
+    peers: Vec<Peer>,
+}
+
+#[derive(Deserialize)]
+struct PaymentList {
+    payments: Vec<Payment>,
+}
+
+#[derive(Deserialize)]
+struct TransferList {
+    transfers: Vec<Transfer>,
+}
+
+#[derive(Deserialize)]
+struct TransactionList {
+    transactions: Vec<Transaction>,
+}
+
+#[derive(Deserialize)]
+struct UnspentList {
+    unspents: Vec<Unspent>,
+}
+
+#[derive(Deserialize)]
+struct AddressResponse {
+    address: String,
+}
+
+/// Client for the REST API of an rgb-lightning-node.
+#[derive(Clone)]
+pub struct NodeClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl NodeClient {
+    pub fn new(base_url: &str) -> Result<Self, NodeClientError> {
+        Self::with_timeout(base_url, DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(base_url: &str, timeout: Duration) -> Result<Self, NodeClientError> {
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(NodeClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        })
+    }
+
+    /// Client for a local node listening on `daemon_listening_port`.
+    pub fn local(daemon_listening_port: &str) -> Result<Self, NodeClientError> {
+        Self::new(&format!("http://localhost:{}", daemon_listening_port))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<String, NodeClientError> {
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status.is_success() {
+            return Ok(text);
+        }
+
+        let message = serde_json::from_str::<ErrorBody>(&text)
+            .map(|body| body.error)
+            .unwrap_or_else(|_| {
+                if text.is_empty() {
+                    status.to_string()
+                } else {
+                    text
+                }
+            });
+        Err(if message.starts_with(LOCKED) {
+            NodeClientError::Locked
+        } else if message.starts_with(NOT_INITIALIZED) {
+            NodeClientError::NotInitialized
+        } else if message == ALREADY_UNLOCKED {
+            NodeClientError::AlreadyUnlocked
+        } else if message == CHANGING_STATE {
+            NodeClientError::ChangingState
+        } else {
+            NodeClientError::Api {
+                status: status.as_u16(),
+                message,
+            }
+        })
+    }
+
+    fn decode<R: DeserializeOwned>(text: &str) -> Result<R, NodeClientError> {
+        serde_json::from_str(text).map_err(|e| NodeClientError::Decode {
+            message: e.to_string(),
+        })
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> Result<R, NodeClientError> {
+        let text = self.send(self.http.get(self.url(path))).await?;
+        Self::decode(&text)
+    }
+
+    async fn post<B: Serialize, R: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<R, NodeClientError> {
+        let text = self.send(self.http.post(self.url(path)).json(body)).await?;
+        Self::decode(&text)
+    }
+
+    // For endpoints answering with an empty object
+    async fn post_empty<B: Serialize>(&self, path: &str, body: &B) -> Result<(), NodeClientError> {
+        self.send(self.http.post(self.url(path)).json(body))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn node_info(&self) -> Result<NodeInfo, NodeClientError> {
+        self.get("/nodeinfo").await
+    }
+
+    pub async fn unlock(&self, request: &UnlockRequest) -> Result<(), NodeClientError> {
+        self.send(
+            self.http
+                .post(self.url("/unlock"))
+                .timeout(UNLOCK_TIMEOUT)
+                .json(request),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn lock(&self) -> Result<(), NodeClientError> {
+        self.post_empty("/lock", &serde_json::json!({})).await
+    }
+
+    /// Writes an encrypted backup of the node to `backup_path`. The node must be locked.
+    pub async fn backup(&self, backup_path: &str, password: &str) -> Result<(), NodeClientError> {
+        self.send(
+            self.http
+                .post(self.url("/backup"))
+                .timeout(BACKUP_TIMEOUT)
+                .json(&serde_json::json!({
+                    "backup_path": backup_path,
+                    "password": password,
+                })),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn btc_balance(&self, skip_sync: bool) -> Result<BtcBalance, NodeClientError> {
+        self.post(
+            "/btcbalance",
+            &serde_json::json!({ "skip_sync": skip_sync }),
+        )
+        .await
+    }
+
+    pub async fn address(&self) -> Result<String, NodeClientError> {
+        let response: AddressResponse = self.post("/address", &serde_json::json!({})).await?;
+        Ok(response.address)
+    }
+
+    pub async fn list_assets(&self) -> Result<Assets, NodeClientError> {
+        self.post(
+            "/listassets",
+            &serde_json::json!({ "filter_asset_schemas": ["Nia"] }),
+        )
+        .await
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, NodeClientError> {
+        let list: ChannelList = self.get("/listchannels").await?;
+        Ok(list.channels)
+    }
+
+    pub async fn list_peers(&self) -> Result<Vec<Peer>, NodeClientError> {
+        let list: PeerList = self.get("/listpeers").await?;
+        Ok(list.peers)
+    }
+
+    pub async fn connect_peer(&self, peer_pubkey_and_addr: &str) -> Result<(), NodeClientError> {
+        self.post_empty(
+            "/connectpeer",
+            &serde_json::json!({ "peer_pubkey_and_addr": peer_pubkey_and_addr }),
+        )
+        .await
+    }
+
+    pub async fn send_payment(
+        &self,
+        invoice: &str,
+    ) -> Result<SendPaymentResponse, NodeClientError> {
+        self.post("/sendpayment", &serde_json::json!({ "invoice": invoice }))
+            .await
+    }
+
+    pub async fn list_payments(&self) -> Result<Vec<Payment>, NodeClientError> {
+        let list: PaymentList = self.get("/listpayments").await?;
+        Ok(list.payments)
+    }
+
+    pub async fn list_transfers(&self, asset_id: &str) -> Result<Vec<Transfer>, NodeClientError> {
+        let list: TransferList = self
+            .post(
+                "/listtransfers",
+                &serde_json::json!({ "asset_id": asset_id }),
+            )
+            .await?;
+        Ok(list.transfers)
+    }
+
+    pub async fn refresh_transfers(&self, skip_sync: bool) -> Result<(), NodeClientError> {
+        self.post_empty(
+            "/refreshtransfers",
+            &serde_json::json!({ "skip_sync": skip_sync }),
+        )
+        .await
+    }
+
+    pub async fn list_transactions(
+        &self,
+        skip_sync: bool,
+    ) -> Result<Vec<Transaction>, NodeClientError> {
+        let list: TransactionList = self
+            .post(
+                "/listtransactions",
+                &serde_json::json!({ "skip_sync": skip_sync }),
+            )
+            .await?;
+        Ok(list.transactions)
+    }
+
+    pub async fn list_swaps(&self) -> Result<Swaps, NodeClientError> {
+        self.get("/listswaps").await
+    }
+
+    pub async fn list_unspents(&self, skip_sync: bool) -> Result<Vec<Unspent>, NodeClientError> {
+        let list: UnspentList = self
+            .post(
+                "/listunspents",
+                &serde_json::json!({ "skip_sync": skip_sync }),
+            )
+            .await?;
+        Ok(list.unspents)
+    }
+
+    pub async fn create_utxos(&self, request: &CreateUtxosRequest) -> Result<(), NodeClientError> {
+        self.post_empty("/createutxos", request).await
+    }
+
+    pub async fn sync(&self) -> Result<(), NodeClientError> {
+        self.post_empty("/sync", &serde_json::json!({})).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn stub(
+        http_method: &str,
+        endpoint: &str,
+        response: ResponseTemplate,
+    ) -> (MockServer, NodeClient) {
+        let server = MockServer::start().await;
+        Mock::given(method(http_method))
+            .and(path(endpoint))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        let client = NodeClient::new(&server.uri()).unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn decodes_node_info() {
+        let (_server, client) = stub(
+            "GET",
+            "/nodeinfo",
+            ResponseTemplate::new(200).set_body_json(json!({
+                "pubkey": "02abc",
+                "num_channels": 2,
+                "num_usable_channels": 1,
+                "local_balance_sat": 5000,
+                "pending_outbound_payments_sat": 0,
+                "num_peers": 3,
+                "onchain_pubkey": "03def",
+                "network_nodes": 10
+            })),
+        )
+        .await;
+
+        let info = client.node_info().await.unwrap();
+        assert_eq!(info.pubkey, "02abc");
+        assert_eq!(info.num_usable_channels, 1);
+        assert_eq!(info.num_peers, 3);
+    }
+
+    #[tokio::test]
+    async fn sends_typed_request_bodies() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/createutxos"))
+            .and(body_json(json!({
+                "up_to": true,
+                "num": 5,
+                "size": 32000,
+                "fee_rate": 5,
+                "skip_sync": false
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = NodeClient::new(&server.uri()).unwrap();
+
+        client
+            .create_utxos(&CreateUtxosRequest {
+                up_to: true,
+                num: Some(5),
+                size: Some(32_000),
+                fee_rate: 5,
+                skip_sync: false,
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn maps_node_errors() {
+        let (_server, client) = stub(
+            "POST",
+            "/btcbalance",
+            ResponseTemplate::new(403).set_body_json(
+                json!({ "error": "Node is locked (hint: call unlock)", "code": 403 }),
+            ),
+        )
+        .await;
+        assert_eq!(
+            client.btc_balance(false).await,
+            Err(NodeClientError::Locked)
+        );
+
+        let (_server, client) = stub(
+            "POST",
+            "/connectpeer",
+            ResponseTemplate::new(400).set_body_json(json!({ "error": "Invalid peer info" })),
+        )
+        .await;
+        assert_eq!(
+            client.connect_peer("02abc@127.0.0.1:9735").await,
+            Err(NodeClientError::Api {
+                status: 400,
+                message: "Invalid peer info".to_string()
+            })
+        );
+
+        let (_server, client) = stub(
+            "GET",
+            "/listchannels",
+            ResponseTemplate::new(200).set_body_string("not json"),
+        )
+        .await;
+        assert!(matches!(
+            client.list_channels().await,
+            Err(NodeClientError::Decode { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn times_out_and_reports_unreachable_nodes() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/createutxos"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .mount(&server)
+            .await;
+        let client = NodeClient::with_timeout(&server.uri(), Duration::from_millis(50)).unwrap();
+        let request = CreateUtxosRequest {
+            up_to: false,
+            num: Some(4),
+            size: Some(32_500),
+            fee_rate: 5,
+            skip_sync: false,
+        };
+        assert_eq!(
+            client.create_utxos(&request).await,
+            Err(NodeClientError::Timeout)
+        );
+
+        // Nothing listens on a port that was just released
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = NodeClient::local(&port.to_string()).unwrap();
+        assert!(matches!(
+            client.list_assets().await,
+            Err(NodeClientError::Connection { .. })
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::clock::now_secs;
+use crate::db;
+use crate::node_client::{Channel, NodeClient, NodeClientError, Transfer};
+use serde::Serialize;
+use std::collections::HashSet;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a `PaymentReceivedEvent` for each new incoming payment.
+pub const PAYMENT_RECEIVED_EVENT: &str = "payment-received";
+/// Emitted with a `TransferSettledEvent` when an RGB transfer settles.
+pub const TRANSFER_SETTLED_EVENT: &str = "transfer-settled";
+/// Emitted with a `ChannelStateChangedEvent` when a channel changes status or closes.
+pub const CHANNEL_STATE_CHANGED_EVENT: &str = "channel-state-changed";
+
+/// Recorded once per account and kind after the first snapshot, which is
+/// stored without emitting so existing history isn't reported as new.
+const BASELINE_ID: &str = "*";
+/// Events the node has not listed for this long are forgotten.
+const SEEN_EVENT_RETENTION_SECS: i64 = 30 * 24 * 60 * 60;
+const CLOSED_STATUS: &str = "Closed";
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentSource {
+    Lightning,
+    Onchain,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PaymentReceivedEvent {
+    pub account_name: String,
+    pub source: PaymentSource,
+    /// Payment hash for lightning payments, txid for on-chain ones.
+    pub id: String,
+    pub amount_msat: Option<u64>,
+    pub asset_id: Option<String>,
+    pub asset_amount: Option<u64>,
+    /// False for on-chain payments still in the mempool.
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TransferSettledEvent {
+    pub account_name: String,
+    pub asset_id: String,
+    pub transfer: Transfer,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChannelStateChangedEvent {
+    pub account_name: String,
+    pub channel_id: String,
+    pub status: String,
+    /// Current channel details, `None` once the channel is closed.
+    pub channel: Option<Channel>,
+}
+
+/// Returns the items whose IDs are not in `seen`. Every listed ID is recorded,
+/// which keeps the ones the node still reports from being pruned. The first
+/// snapshot of a kind is recorded silently.
+fn take_new<T>(
+    account_name: &str,
+    kind: &str,
+    seen: &HashSet<String>,
+    items: Vec<(String, T)>,
+) -> Result<Vec<T>, String> {
+    let baseline = !seen.contains(BASELINE_ID);
+    let mut ids = vec![BASELINE_ID.to_string()];
+    let mut new = Vec::new();
+    for (id, item) in items {
+        if !seen.contains(&id) {
+            new.push(item);
+        }
+        ids.push(id);
+    }
+    db::get()
+        .mark_node_events_seen(account_name, kind, &ids)
+        .map_err(|e| e.to_string())?;
+    Ok(if baseline { Vec::new() } else { new })
+}
+
+fn channel_state_id(channel_id: &str, status: &str) -> String {
+    format!("{}:{}", channel_id, status)
+}
+
+/// Channels that were seen open but are neither listed anymore nor recorded
+/// as closed.
+fn closed_channels(seen: &HashSet<String>, current: &[Channel]) -> Vec<String> {
+    let states: Vec<(&str, &str)> = seen.iter().filter_map(|id| id.rsplit_once(':')).collect();
+    let closed: HashSet<&str> = states
+        .iter()
+        .filter(|(_, status)| *status == CLOSED_STATUS)
+        .map(|(channel_id, _)| *channel_id)
+        .collect();
+    let mut channel_ids: Vec<String> = states
+        .iter()
+        .map(|(channel_id, _)| *channel_id)
+        .filter(|channel_id| !closed.contains(channel_id))
+        .filter(|channel_id| !current.iter().any(|c| c.channel_id == *channel_id))
+        .map(str::to_string)
+        .collect();
+    channel_ids.sort();
+    channel_ids.dedup();
+    channel_ids
+}
+
+#[derive(Debug)]
+enum WatchError {
+    Node(NodeClientError),
+    Db(String),
+}
+
+impl From<NodeClientError> for WatchError {
+    fn from(e: NodeClientError) -> Self {
+        WatchError::Node(e)
+    }
+}
+
+impl From<String> for WatchError {
+    fn from(e: String) -> Self {
+        WatchError::Db(e)
+    }
+}
+
+impl std::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchError::Node(e) => write!(f, "{}", e),
+            WatchError::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+async fn check_payments(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+) -> Result<(), WatchError> {
+    let seen = db::get()
+        .get_seen_node_events(account_name, "payment")
+        .map_err(|e| e.to_string())?;
+    let payments = client
+        .list_payments()
+        .await?
+        .into_iter()
+        .filter(|p| p.inbound && p.status == "Succeeded")
+        .map(|p| {
+            let event = PaymentReceivedEvent {
+                account_name: account_name.to_string(),
+                source: PaymentSource::Lightning,
+                id: p.payment_hash.clone(),
+                amount_msat: p.amt_msat,
+                asset_id: p.asset_id,
+                asset_amount: p.asset_amount,
+                confirmed: true,
+            };
+            (p.payment_hash, event)
+        })
+        .collect();
+    for event in take_new(account_name, "payment", &seen, payments)? {
+        let _ = app_handle.emit(PAYMENT_RECEIVED_EVENT, event);
+    }
+
+    let seen = db::get()
+        .get_seen_node_events(account_name, "transaction")
+        .map_err(|e| e.to_string())?;
+    let transactions = client
+        .list_transactions(true)
+        .await?
+        .into_iter()
+        .filter(|tx| tx.transaction_type == "User" && tx.received > tx.sent)
+        .map(|tx| {
+            let event = PaymentReceivedEvent {
+                account_name: account_name.to_string(),
+                source: PaymentSource::Onchain,
+                id: tx.txid.clone(),
+                amount_msat: Some((tx.received - tx.sent) * 1000),
+                asset_id: None,
+                asset_amount: None,
+                confirmed: tx.confirmation_time.is_some(),
+            };
+            (tx.txid, event)
+        })
+        .collect();
+    for event in take_new(account_name, "transaction", &seen, transactions)? {
+        let _ = app_handle.emit(PAYMENT_RECEIVED_EVENT, event);
+    }
+    Ok(())
+}
+
+fn check_transfers(
+    app_handle: &AppHandle,
+    account_name: &str,
+    transfers: &[(String, Vec<Transfer>)],
+) -> Result<(), WatchError> {
+    let seen = db::get()
+        .get_seen_node_events(account_name, "transfer")
+        .map_err(|e| e.to_string())?;
+    let settled = transfers
+        .iter()
+        .flat_map(|(asset_id, transfers)| {
+            transfers
+                .iter()
+                .filter(|transfer| transfer.status == "Settled")
+                .map(move |transfer| {
+                    let event = TransferSettledEvent {
+                        account_name: account_name.to_string(),
+                        asset_id: asset_id.clone(),
+                        transfer: transfer.clone(),
+                    };
+                    (format!("{}:{}", asset_id, transfer.idx), event)
+                })
+        })
+        .collect();
+    for event in take_new(account_name, "transfer", &seen, settled)? {
+        let _ = app_handle.emit(TRANSFER_SETTLED_EVENT, event);
+    }
+    Ok(())
+}
+
+async fn check_channels(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+) -> Result<(), WatchError> {
+    let seen = db::get()
+        .get_seen_node_events(account_name, "channel_state")
+        .map_err(|e| e.to_string())?;
+    let channels = client.list_channels().await?;
+    let closed = closed_channels(&seen, &channels);
+    let states = channels
+        .into_iter()
+        .map(|channel| {
+            let event = ChannelStateChangedEvent {
+                account_name: account_name.to_string(),
+                channel_id: channel.channel_id.clone(),
+                status: channel.status.clone(),
+                channel: Some(channel),
+            };
+            (channel_state_id(&event.channel_id, &event.status), event)
+        })
+        .chain(closed.into_iter().map(|channel_id| {
+            let event = ChannelStateChangedEvent {
+                account_name: account_name.to_string(),
+                channel_id,
+                status: CLOSED_STATUS.to_string(),
+                channel: None,
+            };
+            (channel_state_id(&event.channel_id, CLOSED_STATUS), event)
+        }))
+        .collect();
+    for event in take_new(account_name, "channel_state", &seen, states)? {
+        let _ = app_handle.emit(CHANNEL_STATE_CHANGED_EVENT, event);
+    }
+    Ok(())
+}
+
+/// Reports incoming payments, settled transfers and channel state changes of
+/// the running node. Called on every wallet sync with the transfers it just
+/// listed, so the node is polled once per cycle. Reported IDs are stored in
+/// the database so each event is emitted once, across restarts, and dropped
+/// once the node stops listing them.
+pub async fn notify(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+    transfers: &[(String, Vec<Transfer>)],
+) {
+    if let Err(e) =
+        db::get().prune_seen_node_events(account_name, now_secs() - SEEN_EVENT_RETENTION_SECS)
+    {
+        println!(
+            "Failed to prune seen node events of {}: {}",
+            account_name, e
+        );
+    }
+    let results = [
+        check_payments(app_handle, client, account_name).await,
+        check_transfers(app_handle, account_name, transfers),
+        check_channels(app_handle, client, account_name).await,
+    ];
+    for e in results.into_iter().filter_map(Result::err) {
+        if !matches!(
+            e,
+            WatchError::Node(NodeClientError::Locked | NodeClientError::ChangingState)
+        ) {
+            println!("Node notifications for {} failed: {}", account_name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(channel_id: &str) -> Channel {
+        Channel {
+            channel_id: channel_id.to_string(),
+            funding_txid: None,
+            peer_pubkey: "02aa".to_string(),
+            status: "Opened".to_string(),
+            ready: true,
+            capacity_sat: 100_000,
+            local_balance_sat: 50_000,
+            outbound_balance_msat: 50_000_000,
+            inbound_balance_msat: 50_000_000,
+            is_usable: true,
+            public: false,
+            asset_id: None,
+            asset_local_amount: None,
+            asset_remote_amount: None,
+        }
+    }
+
+    #[test]
+    fn detects_channels_that_disappeared() {
+        let seen: HashSet<String> = [
+            BASELINE_ID,
+            "c1:Opening",
+            "c1:Opened",
+            "c2:Opened",
+            "c3:Opened",
+            "c3:Closed",
+        ]
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+
+        assert_eq!(closed_channels(&seen, &[channel("c2")]), vec!["c1"]);
+        assert_eq!(closed_channels(&seen, &[]), vec!["c1", "c2"]);
+        assert!(closed_channels(&HashSet::new(), &[]).is_empty());
+    }
+}
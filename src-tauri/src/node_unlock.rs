@@ -0,0 +1,328 @@
+use crate::node_client::{NodeClient, NodeClientError, UnlockRequest};
+use crate::secret_store::SecretStore;
+use crate::{account_settings, db};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Emitted with a `NodeUnlockedEvent` once the backend has unlocked the node.
+pub const NODE_UNLOCKED_EVENT: &str = "node-unlocked";
+/// Emitted with a `NodeUnlockErrorEvent` when auto-unlock gives up.
+pub const NODE_UNLOCK_ERROR_EVENT: &str = "node-unlock-error";
+
+/// How long to wait for the node's API to come up after the process is spawned.
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Tracks the account whose node the backend is unlocking, so the frontend
+/// waits for the outcome instead of calling `/unlock` itself.
+#[derive(Default)]
+pub struct AutoUnlockState {
+    pending: Mutex<Option<String>>,
+}
+
+impl AutoUnlockState {
+    pub fn is_pending(&self, account_name: &str) -> bool {
+        self.pending.lock().unwrap().as_deref() == Some(account_name)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NodeUnlockedEvent {
+    pub account_name: String,
+    /// True if the node was already unlocked when the backend got to it.
+    pub already_unlocked: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NodeUnlockErrorEvent {
+    pub account_name: String,
+    pub error: UnlockError,
+    /// Human readable form of `error`.
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UnlockError {
+    /// The secret store has not been unlocked with the master password.
+    SecretStoreLocked,
+    /// No `node_password` secret is stored for the account.
+    MissingPassword,
+    InvalidRpcUrl {
+        message: String,
+    },
+    /// The node's API did not answer before the timeout.
+    NotReady {
+        timeout_secs: u64,
+    },
+    /// The node process stopped before it could be unlocked.
+    NodeStopped,
+    NotInitialized,
+    WrongPassword,
+    Node {
+        status: u16,
+        message: String,
+    },
+    Request {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for UnlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnlockError::SecretStoreLocked => write!(f, "The secret store is locked"),
+            UnlockError::MissingPassword => write!(f, "No node password is stored"),
+            UnlockError::InvalidRpcUrl { message } => write!(f, "Invalid RPC URL: {}", message),
+            UnlockError::NotReady { timeout_secs } => {
+                write!(f, "The node did not respond within {}s", timeout_secs)
+            }
+            UnlockError::NodeStopped => write!(f, "The node stopped before it was unlocked"),
+            UnlockError::NotInitialized => write!(f, "The wallet has not been initialized"),
+            UnlockError::WrongPassword => write!(f, "Wrong node password"),
+            UnlockError::Node { status, message } => {
+                write!(f, "The node refused to unlock ({}): {}", status, message)
+            }
+            UnlockError::Request { message } => write!(f, "Unlock request failed: {}", message),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct RpcConnection {
+    username: String,
+    password: String,
+    host: String,
+    port: u16,
+}
+
+/// Splits an account's `user:password@host:port` RPC connection URL.
+fn parse_rpc_url(url: &str) -> Result<RpcConnection, UnlockError> {
+    let invalid = |message: &str| UnlockError::InvalidRpcUrl {
+        message: message.to_string(),
+    };
+    let (credentials, host_port) = url
+        .rsplit_once('@')
+        .ok_or_else(|| invalid("expected user:password@host:port"))?;
+    let (username, password) = credentials
+        .split_once(':')
+        .ok_or_else(|| invalid("missing RPC password"))?;
+    let (host, port) = host_port
+        .rsplit_once(':')
+        .ok_or_else(|| invalid("missing RPC port"))?;
+    let port = port.parse().map_err(|_| invalid("invalid RPC port"))?;
+    Ok(RpcConnection {
+        username: username.to_string(),
+        password: password.to_string(),
+        host: host.to_string(),
+        port,
+    })
+}
+
+enum NodeState {
+    Locked,
+    Unlocked,
+    NotInitialized,
+}
+
+impl From<NodeClientError> for UnlockError {
+    fn from(e: NodeClientError) -> Self {
+        match e {
+            NodeClientError::NotInitialized => UnlockError::NotInitialized,
+            NodeClientError::Api { status: 401, .. } => UnlockError::WrongPassword,
+            NodeClientError::Api { status, message } => UnlockError::Node { status, message },
+            e => UnlockError::Request {
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+/// Polls `/nodeinfo` until the API answers. A locked node answers with an
+/// error, which is what we wait for; connection errors mean it is still starting.
+async fn wait_until_ready(
+    client: &NodeClient,
+    is_running: &AtomicBool,
+    timeout: Duration,
+) -> Result<NodeState, UnlockError> {
+    let start = Instant::now();
+    loop {
+        if !is_running.load(Ordering::SeqCst) {
+            return Err(UnlockError::NodeStopped);
+        }
+        match client.node_info().await {
+            Ok(_) => return Ok(NodeState::Unlocked),
+            Err(NodeClientError::NotInitialized) => return Ok(NodeState::NotInitialized),
+            Err(
+                NodeClientError::Connection { .. }
+                | NodeClientError::Timeout
+                | NodeClientError::ChangingState,
+            ) => {}
+            Err(_) => return Ok(NodeState::Locked),
+        }
+        if start.elapsed() >= timeout {
+            return Err(UnlockError::NotReady {
+                timeout_secs: timeout.as_secs(),
+            });
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Builds the `/unlock` body from the account's service settings.
+pub fn unlock_request(
+    account: &db::Account,
+    password: String,
+) -> Result<UnlockRequest, UnlockError> {
+    let rpc = parse_rpc_url(&account.rpc_connection_url)?;
+    Ok(UnlockRequest {
+        password,
+        bitcoind_rpc_username: rpc.username,
+        bitcoind_rpc_password: rpc.password,
+        bitcoind_rpc_host: rpc.host,
+        bitcoind_rpc_port: rpc.port,
+        indexer_url: account.indexer_url.clone(),
+        proxy_endpoint: account.proxy_endpoint.clone(),
+        announce_addresses: Vec::new(),
+        announce_alias: String::new(),
+    })
+}
+
+/// Waits for the node and unlocks it. Returns whether the node was already
+/// unlocked.
+async fn unlock_node(
+    client: &NodeClient,
+    account: &db::Account,
+    password: String,
+    is_running: &AtomicBool,
+) -> Result<bool, UnlockError> {
+    let request = unlock_request(account, password)?;
+
+    match wait_until_ready(client, is_running, READY_TIMEOUT).await? {
+        NodeState::Unlocked => return Ok(true),
+        NodeState::NotInitialized => return Err(UnlockError::NotInitialized),
+        NodeState::Locked => {}
+    }
+
+    match client.unlock(&request).await {
+        Ok(()) => Ok(false),
+        Err(NodeClientError::AlreadyUnlocked) => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Reads the account's node password from the secret store.
+pub fn node_password(app_handle: &AppHandle, account_name: &str) -> Result<String, UnlockError> {
+    let secret_store = app_handle.state::<SecretStore>();
+    if !secret_store.is_unlocked() {
+        return Err(UnlockError::SecretStoreLocked);
+    }
+    secret_store
+        .get(db::get(), account_name, "node_password")
+        .map_err(|message| UnlockError::Request { message })?
+        .ok_or(UnlockError::MissingPassword)
+}
+
+fn emit_result(app_handle: &AppHandle, account_name: String, result: Result<bool, UnlockError>) {
+    match result {
+        Ok(already_unlocked) => {
+            println!("Node unlocked automatically for account: {}", account_name);
+            let _ = app_handle.emit(
+                NODE_UNLOCKED_EVENT,
+                NodeUnlockedEvent {
+                    account_name,
+                    already_unlocked,
+                },
+            );
+        }
+        Err(error) => {
+            println!("Auto-unlock failed for account {}: {}", account_name, error);
+            let _ = app_handle.emit(
+                NODE_UNLOCK_ERROR_EVENT,
+                NodeUnlockErrorEvent {
+                    account_name,
+                    message: error.to_string(),
+                    error,
+                },
+            );
+        }
+    }
+}
+
+/// Unlocks a freshly spawned node in the background if the account opted in
+/// with the `auto_unlock` setting. The outcome is reported with
+/// `node-unlocked` or `node-unlock-error`, and the attempt is pending in
+/// `AutoUnlockState` until then.
+pub fn spawn_auto_unlock(
+    app_handle: AppHandle,
+    account_name: String,
+    daemon_listening_port: String,
+    is_running: Arc<AtomicBool>,
+) {
+    if !account_settings::get_bool(&account_name, "auto_unlock").unwrap_or(false) {
+        return;
+    }
+
+    // Fail right away if the password is not available, before marking it pending
+    let prepared = (|| {
+        let account = db::get()
+            .get_account_by_name(&account_name)
+            .map_err(|e| UnlockError::Request {
+                message: e.to_string(),
+            })?
+            .ok_or_else(|| UnlockError::Request {
+                message: format!("Account not found: {}", account_name),
+            })?;
+        Ok((account, node_password(&app_handle, &account_name)?))
+    })();
+    let (account, password) = match prepared {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            emit_result(&app_handle, account_name, Err(error));
+            return;
+        }
+    };
+    *app_handle
+        .state::<AutoUnlockState>()
+        .pending
+        .lock()
+        .unwrap() = Some(account_name.clone());
+
+    tauri::async_runtime::spawn(async move {
+        let result = match NodeClient::local(&daemon_listening_port) {
+            Ok(client) => unlock_node(&client, &account, password, &is_running).await,
+            Err(e) => Err(e.into()),
+        };
+        app_handle
+            .state::<AutoUnlockState>()
+            .pending
+            .lock()
+            .unwrap()
+            .take_if(|pending| *pending == account_name);
+        emit_result(&app_handle, account_name, result);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rpc_connection_urls() {
+        assert_eq!(
+            parse_rpc_url("user:p@ss@bitcoind.example.com:18443").unwrap(),
+            RpcConnection {
+                username: "user".to_string(),
+                password: "p@ss".to_string(),
+                host: "bitcoind.example.com".to_string(),
+                port: 18443,
+            }
+        );
+        assert!(parse_rpc_url("bitcoind.example.com:18443").is_err());
+        assert!(parse_rpc_url("user@bitcoind.example.com:18443").is_err());
+        assert!(parse_rpc_url("user:password@bitcoind.example.com").is_err());
+    }
+}
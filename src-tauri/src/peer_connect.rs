@@ -0,0 +1,225 @@
+use crate::db::{self, SavedPeer};
+use crate::node_client::NodeClient;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a `PeerConnectionEvent` as saved peers are reconnected.
+pub const PEER_CONNECTION_EVENT: &str = "peer-connection-changed";
+
+const READY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_ATTEMPTS: u32 = 6;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerConnectionStatus {
+    Connected,
+    /// The attempt failed and another one is scheduled.
+    Retrying,
+    /// All attempts failed.
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PeerConnectionEvent {
+    pub account_name: String,
+    pub pubkey: String,
+    pub alias: Option<String>,
+    pub status: PeerConnectionStatus,
+    pub attempt: u32,
+    pub error: Option<String>,
+    pub next_retry_secs: Option<u64>,
+}
+
+/// Checks a peer before it is saved.
+pub fn validate_peer(pubkey: &str, address: &str) -> Result<(), String> {
+    if pubkey.len() != 66 || !pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("The peer pubkey must be 66 hex characters".to_string());
+    }
+    let (host, port) = address
+        .rsplit_once(':')
+        .ok_or_else(|| "The peer address must be host:port".to_string())?;
+    if host.is_empty() || port.parse::<u16>().map(|port| port == 0).unwrap_or(true) {
+        return Err(format!("Invalid peer address: {}", address));
+    }
+    Ok(())
+}
+
+/// Delay before retry number `attempt` (starting at 1), doubling each time.
+fn retry_delay(attempt: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(10))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Sleeps for `duration`, returning early with false if the node stops.
+async fn sleep_while_running(duration: Duration, is_running: &AtomicBool) -> bool {
+    let mut slept = Duration::ZERO;
+    while slept < duration {
+        if !is_running.load(Ordering::SeqCst) {
+            return false;
+        }
+        let step = READY_POLL_INTERVAL.min(duration - slept);
+        tokio::time::sleep(step).await;
+        slept += step;
+    }
+    is_running.load(Ordering::SeqCst)
+}
+
+fn emit(
+    app_handle: &AppHandle,
+    account_name: &str,
+    peer: &SavedPeer,
+    status: PeerConnectionStatus,
+    attempt: u32,
+    error: Option<String>,
+    next_retry: Option<Duration>,
+) {
+    let _ = app_handle.emit(
+        PEER_CONNECTION_EVENT,
+        PeerConnectionEvent {
+            account_name: account_name.to_string(),
+            pubkey: peer.pubkey.clone(),
+            alias: peer.alias.clone(),
+            status,
+            attempt,
+            error,
+            next_retry_secs: next_retry.map(|delay| delay.as_secs()),
+        },
+    );
+}
+
+async fn connect_saved_peers(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+    mut pending: Vec<SavedPeer>,
+    is_running: &AtomicBool,
+) {
+    // Wait until the node is unlocked, however long that takes
+    while client.node_info().await.is_err() {
+        if !sleep_while_running(READY_POLL_INTERVAL, is_running).await {
+            return;
+        }
+    }
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let connected: Vec<String> = client
+            .list_peers()
+            .await
+            .map(|peers| peers.into_iter().map(|peer| peer.pubkey).collect())
+            .unwrap_or_default();
+        let next_retry = (attempt < MAX_ATTEMPTS).then(|| retry_delay(attempt));
+        let mut failed = Vec::new();
+        for peer in pending {
+            let result = if connected.contains(&peer.pubkey) {
+                Ok(())
+            } else {
+                client
+                    .connect_peer(&format!("{}@{}", peer.pubkey, peer.address))
+                    .await
+                    .map_err(|e| e.to_string())
+            };
+            let _ = db::get().record_peer_connection(
+                account_name,
+                &peer.pubkey,
+                result.as_ref().err().map(String::as_str),
+            );
+            match result {
+                Ok(()) => emit(
+                    app_handle,
+                    account_name,
+                    &peer,
+                    PeerConnectionStatus::Connected,
+                    attempt,
+                    None,
+                    None,
+                ),
+                Err(error) => {
+                    let status = if next_retry.is_some() {
+                        PeerConnectionStatus::Retrying
+                    } else {
+                        println!(
+                            "Giving up connecting {} to peer {}: {}",
+                            account_name, peer.pubkey, error
+                        );
+                        PeerConnectionStatus::Failed
+                    };
+                    emit(
+                        app_handle,
+                        account_name,
+                        &peer,
+                        status,
+                        attempt,
+                        Some(error),
+                        next_retry,
+                    );
+                    failed.push(peer);
+                }
+            }
+        }
+        pending = failed;
+        let Some(delay) = next_retry.filter(|_| !pending.is_empty()) else {
+            return;
+        };
+        if !sleep_while_running(delay, is_running).await {
+            return;
+        }
+    }
+}
+
+/// Connects the account's auto-connect peers once a freshly started node is
+/// unlocked, retrying failures with backoff. Progress is reported with
+/// `peer-connection-changed`.
+pub fn spawn_auto_connect(
+    app_handle: AppHandle,
+    account_name: String,
+    daemon_listening_port: String,
+    is_running: Arc<AtomicBool>,
+) {
+    let peers: Vec<SavedPeer> = match db::get().get_saved_peers(&account_name) {
+        Ok(peers) => peers.into_iter().filter(|peer| peer.auto_connect).collect(),
+        Err(e) => {
+            println!("Failed to load saved peers of {}: {}", account_name, e);
+            return;
+        }
+    };
+    if peers.is_empty() {
+        return;
+    }
+    let Ok(client) = NodeClient::local(&daemon_listening_port) else {
+        return;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        connect_saved_peers(&app_handle, &client, &account_name, peers, &is_running).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_saved_peers() {
+        let pubkey = format!("02{}", "ab".repeat(32));
+        assert!(validate_peer(&pubkey, "lsp.example.com:9735").is_ok());
+        assert!(validate_peer(&pubkey, "[::1]:9735").is_ok());
+        assert!(validate_peer("02ab", "lsp.example.com:9735").is_err());
+        assert!(validate_peer(&pubkey, "lsp.example.com").is_err());
+        assert!(validate_peer(&pubkey, ":9735").is_err());
+        assert!(validate_peer(&pubkey, "lsp.example.com:0").is_err());
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(5));
+        assert_eq!(retry_delay(2), Duration::from_secs(10));
+        assert_eq!(retry_delay(5), Duration::from_secs(80));
+        assert_eq!(retry_delay(20), MAX_RETRY_DELAY);
+    }
+}
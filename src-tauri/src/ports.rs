@@ -0,0 +1,35 @@
+use serde::Serialize;
+use std::net::TcpListener;
+
+/// Ports the node uses when an account does not say otherwise.
+pub const DEFAULT_DAEMON_PORT: u16 = 3001;
+pub const DEFAULT_LDK_PEER_PORT: u16 = 9735;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PortPair {
+    pub daemon_listening_port: String,
+    pub ldk_peer_listening_port: String,
+}
+
+/// Check if a port can currently be bound on localhost.
+pub fn is_port_available(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// Returns the first port at or after `start` that is neither reserved nor busy.
+pub fn next_free_port(start: u16, reserved: &[u16]) -> Option<u16> {
+    (start..=u16::MAX).find(|port| !reserved.contains(port) && is_port_available(*port))
+}
+
+/// Suggests a daemon/peer port pair, starting from the given ports, that is not
+/// assigned to another account and can be bound right now.
+pub fn suggest_port_pair(daemon_start: u16, ldk_start: u16, reserved: &[u16]) -> Option<PortPair> {
+    let daemon = next_free_port(daemon_start, reserved)?;
+    let mut reserved = reserved.to_vec();
+    reserved.push(daemon);
+    let ldk = next_free_port(ldk_start, &reserved)?;
+    Some(PortPair {
+        daemon_listening_port: daemon.to_string(),
+        ldk_peer_listening_port: ldk.to_string(),
+    })
+}
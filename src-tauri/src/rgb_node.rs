@@ -1,7 +1,6 @@
-use std::env;
+use serde::Serialize;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::net::TcpListener;
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -12,7 +11,22 @@ use std::time::Duration;
 use tauri::Manager;
 use tauri::{AppHandle, Emitter, WebviewWindow};
 
+use crate::ports::PortPair;
+use crate::{account_settings, app_paths, db, node_unlock, peer_connect, ports};
+
 const SHUTDOWN_TIMEOUT_SECS: u64 = 5;
+/// How often (in monitoring loop iterations of ~1s) the running session is touched in the DB
+const SESSION_HEARTBEAT_TICKS: u64 = 60;
+
+/// Payload of the `node-started` event. The ports are the ones the node was
+/// actually started on, which differ from the account's when auto ports kicked in.
+#[derive(Debug, Serialize, Clone)]
+pub struct NodeStartedEvent {
+    pub account_name: String,
+    pub daemon_listening_port: String,
+    pub ldk_peer_listening_port: String,
+    pub auto_assigned: bool,
+}
 
 #[derive(Debug)]
 enum ControlMessage {
@@ -29,6 +43,8 @@ pub struct NodeProcess {
     shutdown_timeout: Duration,
     current_account: Arc<Mutex<Option<String>>>,
     app_handle: Arc<Mutex<Option<AppHandle>>>,
+    current_session: Arc<Mutex<Option<i64>>>,
+    current_ports: Arc<Mutex<Option<PortPair>>>,
 }
 
 impl NodeProcess {
@@ -44,6 +60,8 @@ impl NodeProcess {
             shutdown_timeout: Duration::from_secs(SHUTDOWN_TIMEOUT_SECS),
             current_account: Arc::new(Mutex::new(None)),
             app_handle: Arc::new(Mutex::new(None)),
+            current_session: Arc::new(Mutex::new(None)),
+            current_ports: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -52,14 +70,10 @@ impl NodeProcess {
         *self.app_handle.lock().unwrap() = Some(window.app_handle().clone());
     }
 
-    /// Check if a port is available
-    fn is_port_available(port: u16) -> bool {
-        TcpListener::bind(("127.0.0.1", port)).is_ok()
-    }
-
     /// Starts a new RGB Lightning Node process (if none is running).
     /// If one is running, it is shut down first, then a new one is started.
-    /// Returns an error if the node binary cannot be started.
+    /// Returns an error if the node binary cannot be started, otherwise the
+    /// ports the node was started on.
     pub fn start(
         &self,
         network: String,
@@ -67,18 +81,39 @@ impl NodeProcess {
         daemon_listening_port: String,
         ldk_peer_listening_port: String,
         account_name: String,
-    ) -> Result<(), String> {
+    ) -> Result<PortPair, String> {
         println!("Starting node for account: {}", account_name);
 
         // Check if ports are available before proceeding
-        let daemon_port = daemon_listening_port
+        let mut daemon_port = daemon_listening_port
             .parse::<u16>()
             .map_err(|e| format!("Invalid daemon port number: {}", e))?;
-        let ldk_port = ldk_peer_listening_port
+        let mut ldk_port = ldk_peer_listening_port
             .parse::<u16>()
             .map_err(|e| format!("Invalid LDK peer port number: {}", e))?;
 
-        if !Self::is_port_available(daemon_port) {
+        // In auto ports mode, busy ports are replaced by free ones instead of failing
+        let mut auto_assigned = false;
+        if (!ports::is_port_available(daemon_port) || !ports::is_port_available(ldk_port))
+            && account_settings::get_bool(&account_name, "auto_ports").unwrap_or(false)
+        {
+            let reserved = db::get()
+                .get_assigned_ports(&account_name)
+                .unwrap_or_default();
+            if let Some(pair) = ports::suggest_port_pair(daemon_port, ldk_port, &reserved) {
+                println!(
+                    "Configured ports {}/{} are busy, using {}/{} instead",
+                    daemon_port, ldk_port, pair.daemon_listening_port, pair.ldk_peer_listening_port
+                );
+                daemon_port = pair.daemon_listening_port.parse().unwrap_or(daemon_port);
+                ldk_port = pair.ldk_peer_listening_port.parse().unwrap_or(ldk_port);
+                auto_assigned = true;
+            }
+        }
+        let daemon_listening_port = daemon_port.to_string();
+        let ldk_peer_listening_port = ldk_port.to_string();
+
+        if !ports::is_port_available(daemon_port) {
             let err = format!("Port {} is already in use. Please make sure no other node is running or try a different port.", daemon_port);
             println!("{}", err);
             if let Some(window) = &*self.window.lock().unwrap() {
@@ -87,7 +122,7 @@ impl NodeProcess {
             return Err(err);
         }
 
-        if !Self::is_port_available(ldk_port) {
+        if !ports::is_port_available(ldk_port) {
             let err = format!("Port {} is already in use. Please make sure no other node is running or try a different port.", ldk_port);
             println!("{}", err);
             if let Some(window) = &*self.window.lock().unwrap() {
@@ -142,25 +177,7 @@ impl NodeProcess {
         }
 
         // 2) Build the final data path for the node
-        let app_data_dir = if cfg!(debug_assertions) {
-            println!("Debug mode: Using local bin directory");
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../bin")
-        } else if cfg!(target_os = "macos") {
-            println!("MacOS: Using Application Support directory");
-            let home =
-                env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
-            PathBuf::from(home).join("Library/Application Support/com.kaleidoswap.dev/data")
-        } else if cfg!(target_os = "windows") {
-            println!("Windows: Using LOCALAPPDATA directory");
-            let local_app_data = env::var("LOCALAPPDATA")
-                .map_err(|e| format!("Failed to get LOCALAPPDATA: {}", e))?;
-            PathBuf::from(local_app_data).join("com.kaleidoswap.dev/data")
-        } else {
-            println!("Linux: Using .local/share directory");
-            let home =
-                env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
-            PathBuf::from(home).join(".local/share/com.kaleidoswap.dev/data")
-        };
+        let app_data_dir = app_paths::get().data_dir.clone();
 
         println!("App data directory: {:?}", app_data_dir);
 
@@ -210,9 +227,50 @@ impl NodeProcess {
 
         println!("Node started successfully for account: {}", account_name);
 
-        // Emit an event so your UI knows a node started
+        // Record the session so the account history knows when it last ran
+        match db::get().start_node_session(
+            &account_name,
+            &daemon_listening_port,
+            &ldk_peer_listening_port,
+        ) {
+            Ok(session_id) => *self.current_session.lock().unwrap() = Some(session_id),
+            Err(e) => println!("Failed to record node session: {}", e),
+        }
+
+        let started_ports = PortPair {
+            daemon_listening_port: daemon_listening_port.clone(),
+            ldk_peer_listening_port: ldk_peer_listening_port.clone(),
+        };
+        *self.current_ports.lock().unwrap() = Some(started_ports.clone());
+
+        // Emit an event so your UI knows a node started (and on which ports)
         if let Some(window) = &*self.window.lock().unwrap() {
-            let _ = window.emit("node-started", ());
+            let _ = window.emit(
+                "node-started",
+                NodeStartedEvent {
+                    account_name: account_name.clone(),
+                    daemon_listening_port: daemon_listening_port.clone(),
+                    ldk_peer_listening_port: ldk_peer_listening_port.clone(),
+                    auto_assigned,
+                },
+            );
+        }
+
+        // Unlock the node from the backend once its API is up, if the account opted in
+        if let Some(app_handle) = &*self.app_handle.lock().unwrap() {
+            node_unlock::spawn_auto_unlock(
+                app_handle.clone(),
+                account_name.clone(),
+                daemon_listening_port.clone(),
+                Arc::clone(&self.is_running),
+            );
+            // Reconnect saved peers once the node is unlocked
+            peer_connect::spawn_auto_connect(
+                app_handle.clone(),
+                account_name.clone(),
+                daemon_listening_port.clone(),
+                Arc::clone(&self.is_running),
+            );
         }
 
         // 5) Spawn a thread to watch the child process output and handle shutdown
@@ -221,6 +279,8 @@ impl NodeProcess {
         let is_running_for_thread = Arc::clone(&self.is_running);
         let logs_for_thread = Arc::clone(&self.logs);
         let window_for_thread = Arc::clone(&self.window);
+        let session_for_thread = Arc::clone(&self.current_session);
+        let ports_for_thread = Arc::clone(&self.current_ports);
         let shutdown_timeout = self.shutdown_timeout;
 
         std::thread::spawn(move || {
@@ -264,12 +324,13 @@ impl NodeProcess {
             drop(child_option); // Release the lock
 
             // Monitoring loop
-            loop {
+            let mut ticks: u64 = 0;
+            let exit_reason = loop {
                 // Check if we got a Stop message
                 match rx.lock().unwrap().try_recv() {
                     Ok(ControlMessage::Stop) => {
                         println!("Received Stop signal, breaking monitoring loop.");
-                        break;
+                        break "stopped".to_string();
                     }
                     Err(_) => {
                         // Check if the child has exited
@@ -277,23 +338,30 @@ impl NodeProcess {
                             match child.try_wait() {
                                 Ok(Some(status)) => {
                                     println!("Node process exited with status: {:?}", status);
-                                    break;
+                                    break format!("exited ({})", status);
                                 }
                                 Ok(None) => {
                                     thread::sleep(Duration::from_secs(1));
                                 }
                                 Err(e) => {
                                     println!("Error waiting for child process: {:?}", e);
-                                    break;
+                                    break format!("error ({})", e);
                                 }
                             }
                         } else {
                             // No child process reference? Possibly already cleaned up
-                            break;
+                            break "killed".to_string();
                         }
                     }
                 }
-            }
+
+                ticks += 1;
+                if ticks.is_multiple_of(SESSION_HEARTBEAT_TICKS) {
+                    if let Some(session_id) = *session_for_thread.lock().unwrap() {
+                        let _ = db::get().touch_node_session(session_id);
+                    }
+                }
+            };
 
             // Graceful shutdown attempt
             {
@@ -327,13 +395,20 @@ impl NodeProcess {
                 }
             }
 
+            if let Some(session_id) = session_for_thread.lock().unwrap().take() {
+                if let Err(e) = db::get().end_node_session(session_id, &exit_reason) {
+                    println!("Failed to record end of node session: {}", e);
+                }
+            }
+
+            *ports_for_thread.lock().unwrap() = None;
             is_running_for_thread.store(false, Ordering::SeqCst);
             if let Some(win) = &*window_for_thread.lock().unwrap() {
                 let _ = win.emit("node-stopped", ());
             }
         });
 
-        Ok(())
+        Ok(started_ports)
     }
 
     /// Requests the process to stop. (Non-blocking)
@@ -393,32 +468,23 @@ impl NodeProcess {
         self.current_account.lock().unwrap().clone()
     }
 
-    /// Returns the path to the log file
-    fn get_log_file_path(&self) -> Result<PathBuf, String> {
-        let log_dir = if cfg!(debug_assertions) {
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("logs")
+    /// Get the ports the running node was started on, if any
+    pub fn get_current_ports(&self) -> Option<PortPair> {
+        if self.is_running() {
+            self.current_ports.lock().unwrap().clone()
         } else {
-            if cfg!(target_os = "macos") {
-                // macOS: ~/Library/Logs/com.kaleidoswap.dev/
-                let home =
-                    env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
-                PathBuf::from(home).join("Library/Logs/com.kaleidoswap.dev")
-            } else if cfg!(target_os = "windows") {
-                // Windows: %APPDATA%\com.kaleidoswap.dev\logs
-                let app_data = env::var("APPDATA")
-                    .map_err(|e| format!("Failed to get APPDATA directory: {}", e))?;
-                PathBuf::from(app_data)
-                    .join("com.kaleidoswap.dev")
-                    .join("logs")
-            } else {
-                // Linux: ~/.local/share/com.kaleidoswap.dev/logs
-                let home =
-                    env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
-                PathBuf::from(home).join(".local/share/com.kaleidoswap.dev/logs")
-            }
-        };
+            None
+        }
+    }
+
+    /// Get the account and ports of the running node, if any
+    pub fn running_node(&self) -> Option<(String, PortPair)> {
+        self.get_current_account().zip(self.get_current_ports())
+    }
 
-        Ok(log_dir.join("rgb-lightning-node.log"))
+    /// Returns the path to the log file
+    fn get_log_file_path(&self) -> PathBuf {
+        app_paths::get().node_log_path()
     }
 
     /// Returns any logs captured so far, including those from the log file
@@ -429,13 +495,11 @@ impl NodeProcess {
         logs.extend(self.logs.lock().unwrap().clone());
 
         // Then try to read from the log file
-        if let Ok(log_path) = self.get_log_file_path() {
-            if let Ok(file) = File::open(log_path) {
-                let reader = BufReader::new(file);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        logs.push(line);
-                    }
+        if let Ok(file) = File::open(self.get_log_file_path()) {
+            let reader = BufReader::new(file);
+            for line in reader.lines() {
+                if let Ok(line) = line {
+                    logs.push(line);
                 }
             }
         }
@@ -552,34 +616,11 @@ impl NodeProcess {
         }
 
         // Set up logging directory
-        let log_dir = if cfg!(debug_assertions) {
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("logs")
-        } else {
-            if cfg!(target_os = "macos") {
-                // macOS: ~/Library/Logs/com.kaleidoswap.dev/
-                let home =
-                    env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
-                PathBuf::from(home).join("Library/Logs/com.kaleidoswap.dev")
-            } else if cfg!(target_os = "windows") {
-                // Windows: %APPDATA%\com.kaleidoswap.dev\logs
-                let app_data = env::var("APPDATA")
-                    .map_err(|e| format!("Failed to get APPDATA directory: {}", e))?;
-                PathBuf::from(app_data)
-                    .join("com.kaleidoswap.dev")
-                    .join("logs")
-            } else {
-                // Linux: ~/.local/share/com.kaleidoswap.dev/logs
-                let home =
-                    env::var("HOME").map_err(|e| format!("Failed to get HOME directory: {}", e))?;
-                PathBuf::from(home).join(".local/share/com.kaleidoswap.dev/logs")
-            }
-        };
-
-        // Ensure log directory exists
-        std::fs::create_dir_all(&log_dir)
+        let paths = app_paths::get();
+        std::fs::create_dir_all(&paths.log_dir)
             .map_err(|e| format!("Failed to create log directory: {}", e))?;
 
-        let log_file = log_dir.join("rgb-lightning-node.log");
+        let log_file = paths.node_log_path();
         println!("Log file path: {:?}", log_file);
 
         // Open log file for writing
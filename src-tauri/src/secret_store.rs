@@ -0,0 +1,379 @@
+use crate::db::{self, Db, SecretStoreConfig};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::Serialize;
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+/// Emitted with the new `SecretStoreStatus` when the store is locked or unlocked.
+pub const SECRET_STORE_EVENT: &str = "secret-store-changed";
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const MIN_PASSWORD_LEN: usize = 8;
+/// Encrypted with the derived key to check the master password on unlock.
+const VERIFIER_PLAINTEXT: &[u8] = b"kaleidoswap-secret-store-v1";
+
+/// Secrets an account can hold. Maker credentials are stored per maker URL
+/// as `maker_credential:<url>`.
+const SECRET_KEYS: &[&str] = &["node_password", "api_token"];
+const MAKER_CREDENTIAL_PREFIX: &str = "maker_credential:";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SecretStoreStatus {
+    pub initialized: bool,
+    pub unlocked: bool,
+}
+
+/// Holds the key derived from the master password while the store is unlocked.
+/// Values are only ever written to the DB encrypted with XChaCha20-Poly1305.
+#[derive(Default)]
+pub struct SecretStore {
+    key: Mutex<Option<Zeroizing<[u8; KEY_LEN]>>>,
+}
+
+/// Checks that `key` names a known kind of secret.
+pub fn validate_key(key: &str) -> Result<(), String> {
+    if SECRET_KEYS.contains(&key) {
+        return Ok(());
+    }
+    match key.strip_prefix(MAKER_CREDENTIAL_PREFIX) {
+        Some(url) if !url.trim().is_empty() => Ok(()),
+        _ => Err(format!(
+            "Unknown secret '{}' (expected {} or {}<maker url>)",
+            key,
+            SECRET_KEYS.join(", "),
+            MAKER_CREDENTIAL_PREFIX
+        )),
+    }
+}
+
+fn derive_key(
+    password: &str,
+    config: &SecretStoreConfig,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
+    let params = Params::new(
+        config.memory_kib,
+        config.iterations,
+        config.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), &config.salt, key.as_mut())
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(
+    key: &[u8; KEY_LEN],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+fn decrypt(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, String> {
+    if nonce.len() != 24 {
+        return Err("Corrupt secret: invalid nonce".to_string());
+    }
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| "Failed to decrypt secret".to_string())
+}
+
+// Binds a ciphertext to its row, so values cannot be swapped between accounts or keys.
+fn secret_aad(account_id: i32, key: &str) -> Vec<u8> {
+    format!("{}:{}", account_id, key).into_bytes()
+}
+
+fn new_config(password: &str) -> Result<(SecretStoreConfig, Zeroizing<[u8; KEY_LEN]>), String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!(
+            "The master password must have at least {} characters",
+            MIN_PASSWORD_LEN
+        ));
+    }
+
+    let mut salt = vec![0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut config = SecretStoreConfig {
+        salt,
+        memory_kib: Params::DEFAULT_M_COST,
+        iterations: Params::DEFAULT_T_COST,
+        parallelism: Params::DEFAULT_P_COST,
+        verifier_nonce: Vec::new(),
+        verifier: Vec::new(),
+    };
+    let key = derive_key(password, &config)?;
+    let (nonce, verifier) = encrypt(&key, VERIFIER_PLAINTEXT, b"verifier")?;
+    config.verifier_nonce = nonce;
+    config.verifier = verifier;
+    Ok((config, key))
+}
+
+impl SecretStore {
+    pub fn status(&self, db: &Db) -> Result<SecretStoreStatus, String> {
+        Ok(SecretStoreStatus {
+            initialized: db
+                .get_secret_store_config()
+                .map_err(|e| e.to_string())?
+                .is_some(),
+            unlocked: self.is_unlocked(),
+        })
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.lock().unwrap().is_some()
+    }
+
+    /// Sets the master password the first time and leaves the store unlocked.
+    pub fn setup(&self, db: &Db, password: &str) -> Result<(), String> {
+        if db
+            .get_secret_store_config()
+            .map_err(|e| e.to_string())?
+            .is_some()
+        {
+            return Err("A master password is already set".to_string());
+        }
+        let (config, key) = new_config(password)?;
+        db.set_secret_store_config(&config)
+            .map_err(|e| e.to_string())?;
+        *self.key.lock().unwrap() = Some(key);
+        println!("Secret store initialized");
+        Ok(())
+    }
+
+    /// Derives the key from the master password and keeps it in memory.
+    pub fn unlock(&self, db: &Db, password: &str) -> Result<(), String> {
+        let config = db
+            .get_secret_store_config()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "No master password has been set".to_string())?;
+        let key = derive_key(password, &config)?;
+        decrypt(&key, &config.verifier_nonce, &config.verifier, b"verifier")
+            .map_err(|_| "Wrong master password".to_string())?;
+        *self.key.lock().unwrap() = Some(key);
+        println!("Secret store unlocked");
+        Ok(())
+    }
+
+    /// Drops the key from memory. Secrets cannot be read until the next unlock.
+    pub fn lock(&self) {
+        self.key.lock().unwrap().take();
+        println!("Secret store locked");
+    }
+
+    /// Re-encrypts every secret under a key derived from the new password.
+    pub fn change_password(
+        &self,
+        db: &Db,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), String> {
+        self.unlock(db, old_password)?;
+        let old_key = self.key.lock().unwrap().clone().unwrap();
+        let (config, new_key) = new_config(new_password)?;
+
+        let mut secrets = Vec::new();
+        for secret in db.get_all_secrets().map_err(|e| e.to_string())? {
+            let aad = secret_aad(secret.account_id, &secret.key);
+            let plaintext = decrypt(&old_key, &secret.nonce, &secret.ciphertext, &aad)?;
+            let (nonce, ciphertext) = encrypt(&new_key, &plaintext, &aad)?;
+            secrets.push(db::StoredSecret {
+                nonce,
+                ciphertext,
+                ..secret
+            });
+        }
+        db.replace_secret_store(&config, &secrets)
+            .map_err(|e| e.to_string())?;
+
+        *self.key.lock().unwrap() = Some(new_key);
+        println!("Master password changed");
+        Ok(())
+    }
+
+    fn key(&self) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
+        self.key
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| "The secret store is locked".to_string())
+    }
+
+    pub fn set(&self, db: &Db, account_name: &str, key: &str, value: &str) -> Result<(), String> {
+        validate_key(key)?;
+        let secret_key = self.key()?;
+        let account_id = db
+            .get_account_by_name(account_name)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Account not found: {}", account_name))?
+            .id;
+        let (nonce, ciphertext) =
+            encrypt(&secret_key, value.as_bytes(), &secret_aad(account_id, key))?;
+        db.set_secret(account_name, key, &nonce, &ciphertext)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn get(&self, db: &Db, account_name: &str, key: &str) -> Result<Option<String>, String> {
+        let secret_key = self.key()?;
+        let Some(secret) = db
+            .get_secret(account_name, key)
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+        let plaintext = decrypt(
+            &secret_key,
+            &secret.nonce,
+            &secret.ciphertext,
+            &secret_aad(secret.account_id, &secret.key),
+        )?;
+        String::from_utf8(plaintext.to_vec())
+            .map(Some)
+            .map_err(|_| "Corrupt secret: not valid UTF-8".to_string())
+    }
+
+    /// Removes a secret. Like writing one, this needs the store unlocked.
+    pub fn delete(&self, db: &Db, account_name: &str, key: &str) -> Result<usize, String> {
+        self.key()?;
+        db.delete_secret(account_name, key)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Names of the secrets saved for an account. Only the names are stored in
+    /// clear, so this also works while the store is locked.
+    pub fn list(&self, db: &Db, account_name: &str) -> Result<Vec<String>, String> {
+        db.list_secret_keys(account_name).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::AccountFields;
+
+    fn db_with_account() -> Db {
+        let db = Db::open_in_memory().unwrap();
+        db.insert_account(
+            &AccountFields {
+                name: "alice".to_string(),
+                network: "Regtest".to_string(),
+                datapath: Some(String::new()),
+                rpc_connection_url: String::new(),
+                node_url: String::new(),
+                indexer_url: String::new(),
+                proxy_endpoint: String::new(),
+                default_lsp_url: String::new(),
+                maker_urls: String::new(),
+                default_maker_url: String::new(),
+                daemon_listening_port: "3001".to_string(),
+                ldk_peer_listening_port: "9735".to_string(),
+            },
+            "test",
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn secrets_are_encrypted_and_need_the_master_password() {
+        let db = db_with_account();
+        let store = SecretStore::default();
+        assert!(store
+            .set(&db, "alice", "node_password", "hunter22")
+            .is_err());
+
+        store.setup(&db, "correct horse").unwrap();
+        store
+            .set(&db, "alice", "node_password", "hunter22")
+            .unwrap();
+        let stored = db.get_secret("alice", "node_password").unwrap().unwrap();
+        assert!(!stored
+            .ciphertext
+            .windows(8)
+            .any(|window| window == b"hunter22"));
+
+        store.lock();
+        assert!(store.get(&db, "alice", "node_password").is_err());
+        assert!(store.delete(&db, "alice", "node_password").is_err());
+        assert_eq!(store.list(&db, "alice").unwrap(), vec!["node_password"]);
+        assert!(store.unlock(&db, "wrong password").is_err());
+        assert!(!store.is_unlocked());
+
+        store.unlock(&db, "correct horse").unwrap();
+        assert_eq!(
+            store.get(&db, "alice", "node_password").unwrap().as_deref(),
+            Some("hunter22")
+        );
+        assert_eq!(store.get(&db, "alice", "api_token").unwrap(), None);
+    }
+
+    #[test]
+    fn change_password_reencrypts_secrets() {
+        let db = db_with_account();
+        let store = SecretStore::default();
+        store.setup(&db, "first password").unwrap();
+        store
+            .set(&db, "alice", "maker_credential:http://maker", "token")
+            .unwrap();
+
+        assert!(store
+            .change_password(&db, "not the password", "second password")
+            .is_err());
+        store
+            .change_password(&db, "first password", "second password")
+            .unwrap();
+        store.lock();
+        assert!(store.unlock(&db, "first password").is_err());
+        store.unlock(&db, "second password").unwrap();
+        assert_eq!(
+            store
+                .get(&db, "alice", "maker_credential:http://maker")
+                .unwrap()
+                .as_deref(),
+            Some("token")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_short_passwords() {
+        let db = db_with_account();
+        let store = SecretStore::default();
+        assert!(store.setup(&db, "short").is_err());
+        store.setup(&db, "long enough").unwrap();
+        assert!(store.setup(&db, "long enough").is_err());
+        assert!(store.set(&db, "alice", "seed", "x").is_err());
+        assert!(store.set(&db, "alice", "maker_credential:", "x").is_err());
+        assert!(store.set(&db, "nobody", "api_token", "x").is_err());
+    }
+}
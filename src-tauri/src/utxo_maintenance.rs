@@ -0,0 +1,185 @@
+use crate::node_client::{CreateUtxosRequest, NodeClient, Unspent};
+use crate::rgb_node::NodeProcess;
+use crate::{account_settings, background, db};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a `UtxoMaintenanceEvent` each time UTXOs are created or the
+/// attempt fails.
+pub const UTXO_MAINTENANCE_EVENT: &str = "utxo-maintenance";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Wait after a failed attempt, e.g. while the wallet lacks funds.
+const RETRY_AFTER_FAILURE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UtxoMaintenanceEvent {
+    pub account_name: String,
+    pub entry: db::UtxoMaintenanceEntry,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct UtxoPolicy {
+    min_free: u32,
+    target: u8,
+    size_sat: u32,
+    fee_rate: u64,
+}
+
+impl UtxoPolicy {
+    /// The account's policy, `None` if maintenance is off.
+    fn load(account_name: &str) -> Result<Option<Self>, String> {
+        if !account_settings::get_bool(account_name, "utxo_maintenance")? {
+            return Ok(None);
+        }
+        let min_free = account_settings::get_i64(account_name, "utxo_min_free")?;
+        let target = account_settings::get_i64(account_name, "utxo_target_free")?;
+        Ok(Some(UtxoPolicy {
+            min_free: min_free as u32,
+            target: target.max(min_free).min(u8::MAX as i64) as u8,
+            size_sat: account_settings::get_i64(account_name, "utxo_size_sat")? as u32,
+            fee_rate: account_settings::get_i64(account_name, "utxo_fee_rate")? as u64,
+        }))
+    }
+}
+
+/// Colorable UTXOs without any RGB allocation, which new RGB operations need.
+fn free_colored_utxos(unspents: &[Unspent]) -> u32 {
+    unspents
+        .iter()
+        .filter(|unspent| unspent.utxo.colorable && unspent.rgb_allocations.is_empty())
+        .count() as u32
+}
+
+/// Tops up the free colored UTXOs if they dropped below the policy's minimum.
+/// Returns the logged entry when an attempt was made.
+async fn maintain(
+    client: &NodeClient,
+    account_name: &str,
+    policy: &UtxoPolicy,
+) -> Result<Option<db::UtxoMaintenanceEntry>, String> {
+    let unspents = client
+        .list_unspents(true)
+        .await
+        .map_err(|e| e.to_string())?;
+    let free = free_colored_utxos(&unspents);
+    if free >= policy.min_free {
+        return Ok(None);
+    }
+
+    println!(
+        "Account {} has {} free colored UTXOs, creating up to {}",
+        account_name, free, policy.target
+    );
+    let error = client
+        .create_utxos(&CreateUtxosRequest {
+            up_to: true,
+            num: Some(policy.target),
+            size: Some(policy.size_sat),
+            fee_rate: policy.fee_rate,
+            skip_sync: true,
+        })
+        .await
+        .err()
+        .map(|e| e.to_string());
+    if let Some(error) = &error {
+        println!("Creating UTXOs for {} failed: {}", account_name, error);
+    }
+
+    db::get()
+        .insert_utxo_maintenance(
+            account_name,
+            free as i64,
+            policy.target as i64,
+            policy.size_sat as i64,
+            policy.fee_rate as i64,
+            error.as_deref(),
+        )
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// Starts the background loop that keeps enough free colored UTXOs for the
+/// running node's account, following its `utxo_*` settings.
+pub fn spawn_maintainer(app_handle: AppHandle, node_process: Arc<Mutex<NodeProcess>>) {
+    tauri::async_runtime::spawn(async move {
+        let mut retry_after: Option<(String, Instant)> = None;
+        loop {
+            let Some(node) = background::next_tick(&node_process, CHECK_INTERVAL).await else {
+                continue;
+            };
+            let account_name = node.account_name.clone();
+            if let Some((account, until)) = &retry_after {
+                if *account == account_name && Instant::now() < *until {
+                    continue;
+                }
+            }
+            let policy = match UtxoPolicy::load(&account_name) {
+                Ok(Some(policy)) => policy,
+                Ok(None) => continue,
+                Err(e) => {
+                    println!("Failed to load the UTXO policy of {}: {}", account_name, e);
+                    continue;
+                }
+            };
+            let Some(client) = node.unlocked_client().await else {
+                continue;
+            };
+
+            match maintain(&client, &account_name, &policy).await {
+                Ok(Some(entry)) => {
+                    retry_after = entry
+                        .error
+                        .is_some()
+                        .then(|| (account_name.clone(), Instant::now() + RETRY_AFTER_FAILURE));
+                    let _ = app_handle.emit(
+                        UTXO_MAINTENANCE_EVENT,
+                        UtxoMaintenanceEvent {
+                            account_name,
+                            entry,
+                        },
+                    );
+                }
+                Ok(None) => retry_after = None,
+                Err(e) => println!("UTXO maintenance for {} failed: {}", account_name, e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_client::{RgbAllocation, Utxo};
+
+    fn unspent(colorable: bool, allocations: usize) -> Unspent {
+        Unspent {
+            utxo: Utxo {
+                outpoint: "txid:0".to_string(),
+                btc_amount: 32_500,
+                colorable,
+            },
+            rgb_allocations: (0..allocations)
+                .map(|_| RgbAllocation {
+                    asset_id: Some("rgb:abc".to_string()),
+                    amount: 10,
+                    settled: true,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn counts_only_free_colorable_utxos() {
+        let unspents = vec![
+            unspent(true, 0),
+            unspent(true, 0),
+            unspent(true, 1),
+            unspent(false, 0),
+        ];
+        assert_eq!(free_colored_utxos(&unspents), 2);
+        assert_eq!(free_colored_utxos(&[]), 0);
+    }
+}
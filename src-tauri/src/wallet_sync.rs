@@ -0,0 +1,379 @@
+use crate::background::{self, RunningNode};
+use crate::node_client::{Balance, BtcBalance, NodeClient, NodeClientError, Transfer};
+use crate::rgb_node::NodeProcess;
+use crate::{account_settings, history, node_notifications};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a `WalletSyncedEvent` after every successful wallet sync.
+pub const WALLET_SYNCED_EVENT: &str = "wallet-synced";
+/// Emitted with a `TransfersUpdatedEvent` when RGB transfers appear or change status.
+pub const TRANSFERS_UPDATED_EVENT: &str = "transfers-updated";
+
+/// Poll interval while no node is running or the node is locked.
+const IDLE_INTERVAL: Duration = Duration::from_secs(15);
+/// Floor of the adaptive interval, used while transfers are pending.
+const MIN_INTERVAL: Duration = Duration::from_secs(15);
+/// Ceiling of the error backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+/// Quiet cycles after which the interval starts stretching.
+const QUIET_CYCLES: u32 = 5;
+
+const PENDING_STATUSES: &[&str] = &["WaitingCounterparty", "WaitingConfirmations"];
+
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct BalanceDelta {
+    pub settled: i64,
+    pub future: i64,
+    pub spendable: i64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct BtcBalanceDelta {
+    pub vanilla: BalanceDelta,
+    pub colored: BalanceDelta,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WalletSyncedEvent {
+    pub account_name: String,
+    pub balance: BtcBalance,
+    /// Change since the previous sync, `None` on the first sync of a session.
+    pub delta: Option<BtcBalanceDelta>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TransferChange {
+    pub asset_id: String,
+    pub transfer: Transfer,
+    /// Status seen on the previous refresh, `None` for a new transfer.
+    pub previous_status: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TransfersUpdatedEvent {
+    pub account_name: String,
+    pub changes: Vec<TransferChange>,
+}
+
+fn balance_delta(before: &Balance, after: &Balance) -> BalanceDelta {
+    BalanceDelta {
+        settled: after.settled as i64 - before.settled as i64,
+        future: after.future as i64 - before.future as i64,
+        spendable: after.spendable as i64 - before.spendable as i64,
+    }
+}
+
+fn btc_balance_delta(before: &BtcBalance, after: &BtcBalance) -> BtcBalanceDelta {
+    BtcBalanceDelta {
+        vanilla: balance_delta(&before.vanilla, &after.vanilla),
+        colored: balance_delta(&before.colored, &after.colored),
+    }
+}
+
+/// Transfers that are new or whose status changed since `known` was taken.
+/// `known` is updated in place.
+fn transfer_changes(
+    known: &mut HashMap<(String, i64), String>,
+    asset_id: &str,
+    transfers: Vec<Transfer>,
+) -> Vec<TransferChange> {
+    let mut changes = Vec::new();
+    for transfer in transfers {
+        let key = (asset_id.to_string(), transfer.idx);
+        let previous_status = known.get(&key).cloned();
+        if previous_status.as_deref() == Some(transfer.status.as_str()) {
+            continue;
+        }
+        known.insert(key, transfer.status.clone());
+        changes.push(TransferChange {
+            asset_id: asset_id.to_string(),
+            transfer,
+            previous_status,
+        });
+    }
+    changes
+}
+
+/// Next delay after a successful cycle: short while transfers are pending,
+/// the configured interval normally, and up to four times longer once
+/// nothing has changed for a while.
+fn next_interval(base: Duration, pending_transfers: bool, quiet_cycles: u32) -> Duration {
+    if pending_transfers {
+        return (base / 4).max(MIN_INTERVAL).min(base);
+    }
+    if quiet_cycles < QUIET_CYCLES {
+        return base;
+    }
+    let stretch = 1 + (quiet_cycles - QUIET_CYCLES + 1).min(3);
+    base * stretch
+}
+
+/// Exponential backoff after consecutive failures.
+fn backoff(base: Duration, failures: u32) -> Duration {
+    base.saturating_mul(1 << failures.min(10)).min(MAX_BACKOFF)
+}
+
+#[derive(Default)]
+struct SyncState {
+    account_name: Option<String>,
+    balance: Option<BtcBalance>,
+    transfers: HashMap<(String, i64), String>,
+    /// Set once the first transfer refresh of the session went through.
+    transfers_initialized: bool,
+    quiet_cycles: u32,
+    failures: u32,
+}
+
+impl SyncState {
+    /// Records the transfers of every asset and returns what changed. The
+    /// first refresh of a session only learns the existing transfers.
+    fn record_transfers(&mut self, snapshot: Vec<(String, Vec<Transfer>)>) -> Vec<TransferChange> {
+        let mut changes = Vec::new();
+        for (asset_id, transfers) in snapshot {
+            changes.extend(transfer_changes(&mut self.transfers, &asset_id, transfers));
+        }
+        if !self.transfers_initialized {
+            self.transfers_initialized = true;
+            return Vec::new();
+        }
+        changes
+    }
+}
+
+struct CycleOutcome {
+    pending_transfers: bool,
+    changed: bool,
+}
+
+async fn sync_cycle(
+    app_handle: &AppHandle,
+    client: &NodeClient,
+    account_name: &str,
+    state: &mut SyncState,
+) -> Result<CycleOutcome, NodeClientError> {
+    client.sync().await?;
+    let balance = client.btc_balance(true).await?;
+    let delta = state
+        .balance
+        .as_ref()
+        .map(|before| btc_balance_delta(before, &balance));
+    let mut changed = delta
+        .as_ref()
+        .is_some_and(|delta| *delta != BtcBalanceDelta::default());
+    state.balance = Some(balance.clone());
+    let _ = app_handle.emit(
+        WALLET_SYNCED_EVENT,
+        WalletSyncedEvent {
+            account_name: account_name.to_string(),
+            balance,
+            delta,
+        },
+    );
+
+    client.refresh_transfers(true).await?;
+    let mut snapshot = Vec::new();
+    let mut pending_transfers = false;
+    for asset in client.list_assets().await?.nia {
+        let transfers = client.list_transfers(&asset.asset_id).await?;
+        pending_transfers |= transfers
+            .iter()
+            .any(|transfer| PENDING_STATUSES.contains(&transfer.status.as_str()));
+        snapshot.push((asset.asset_id, transfers));
+    }
+    node_notifications::notify(app_handle, client, account_name, &snapshot).await;
+    let changes = state.record_transfers(snapshot);
+    if !changes.is_empty() {
+        changed = true;
+        let _ = app_handle.emit(
+            TRANSFERS_UPDATED_EVENT,
+            TransfersUpdatedEvent {
+                account_name: account_name.to_string(),
+                changes,
+            },
+        );
+    }
+
+    Ok(CycleOutcome {
+        pending_transfers,
+        changed,
+    })
+}
+
+/// Starts the background loop that syncs the wallet, refreshes RGB transfers,
+/// reports node events and updates the local history of the running node
+/// while it is unlocked.
+/// The interval comes from the account's `sync_interval_secs` setting and
+/// adapts to pending transfers and errors.
+pub fn spawn_scheduler(app_handle: AppHandle, node_process: Arc<Mutex<NodeProcess>>) {
+    tauri::async_runtime::spawn(async move {
+        let mut state = SyncState::default();
+        let mut delay = IDLE_INTERVAL;
+        loop {
+            let running = background::next_tick(&node_process, delay).await;
+            let Some(RunningNode {
+                account_name,
+                ports,
+            }) = running
+            else {
+                state = SyncState::default();
+                delay = IDLE_INTERVAL;
+                continue;
+            };
+            if state.account_name.as_deref() != Some(account_name.as_str()) {
+                state = SyncState {
+                    account_name: Some(account_name.clone()),
+                    ..SyncState::default()
+                };
+            }
+
+            let base = Duration::from_secs(
+                account_settings::get_i64(&account_name, "sync_interval_secs")
+                    .unwrap_or(60)
+                    .max(1) as u64,
+            );
+            let Ok(client) = NodeClient::local(&ports.daemon_listening_port) else {
+                delay = base;
+                continue;
+            };
+
+            delay = match sync_cycle(&app_handle, &client, &account_name, &mut state).await {
+                Ok(outcome) => {
+                    if let Err(e) = history::sync(&app_handle, &client, &account_name).await {
+                        println!("History sync for {} failed: {}", account_name, e);
+                    }
+                    state.failures = 0;
+                    state.quiet_cycles = if outcome.changed {
+                        0
+                    } else {
+                        state.quiet_cycles.saturating_add(1)
+                    };
+                    next_interval(base, outcome.pending_transfers, state.quiet_cycles)
+                }
+                // Not an error: the node is locked, starting or being backed up
+                Err(
+                    NodeClientError::Locked
+                    | NodeClientError::NotInitialized
+                    | NodeClientError::ChangingState
+                    | NodeClientError::Connection { .. },
+                ) => IDLE_INTERVAL,
+                Err(e) => {
+                    state.failures += 1;
+                    println!(
+                        "Wallet sync for {} failed ({} in a row): {}",
+                        account_name, state.failures, e
+                    );
+                    backoff(base, state.failures)
+                }
+            };
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(idx: i64, status: &str) -> Transfer {
+        Transfer {
+            idx,
+            created_at: 0,
+            updated_at: 0,
+            status: status.to_string(),
+            amount: 10,
+            kind: "ReceiveWitness".to_string(),
+            txid: None,
+            recipient_id: None,
+        }
+    }
+
+    #[test]
+    fn reports_new_and_changed_transfers_only() {
+        let mut known = HashMap::new();
+        let changes = transfer_changes(
+            &mut known,
+            "rgb:a",
+            vec![transfer(1, "WaitingCounterparty"), transfer(2, "Settled")],
+        );
+        assert_eq!(changes.len(), 2);
+
+        let changes = transfer_changes(
+            &mut known,
+            "rgb:a",
+            vec![transfer(1, "WaitingConfirmations"), transfer(2, "Settled")],
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].transfer.idx, 1);
+        assert_eq!(
+            changes[0].previous_status.as_deref(),
+            Some("WaitingCounterparty")
+        );
+
+        // The same index on another asset is a different transfer
+        assert_eq!(
+            transfer_changes(&mut known, "rgb:b", vec![transfer(1, "Settled")]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn first_transfer_of_an_empty_wallet_is_reported() {
+        let mut state = SyncState::default();
+        assert!(state.record_transfers(Vec::new()).is_empty());
+
+        let changes =
+            state.record_transfers(vec![("rgb:a".to_string(), vec![transfer(1, "Settled")])]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous_status, None);
+
+        // A wallet that already had transfers only learns them on the first refresh
+        let mut state = SyncState::default();
+        let snapshot = vec![("rgb:a".to_string(), vec![transfer(1, "Settled")])];
+        assert!(state.record_transfers(snapshot.clone()).is_empty());
+        assert!(state.record_transfers(snapshot).is_empty());
+    }
+
+    #[test]
+    fn interval_adapts_to_activity_and_errors() {
+        let base = Duration::from_secs(120);
+        assert_eq!(next_interval(base, true, 0), Duration::from_secs(30));
+        assert_eq!(
+            next_interval(Duration::from_secs(20), true, 0),
+            Duration::from_secs(15)
+        );
+        assert_eq!(
+            next_interval(Duration::from_secs(10), true, 0),
+            Duration::from_secs(10)
+        );
+        assert_eq!(next_interval(base, false, 0), base);
+        assert_eq!(next_interval(base, false, QUIET_CYCLES), base * 2);
+        assert_eq!(next_interval(base, false, 100), base * 4);
+
+        assert_eq!(backoff(base, 1), Duration::from_secs(240));
+        assert_eq!(backoff(base, 3), Duration::from_secs(960));
+        assert_eq!(backoff(base, 30), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn computes_signed_balance_deltas() {
+        let balance = |settled, spendable| BtcBalance {
+            vanilla: Balance {
+                settled,
+                future: settled,
+                spendable,
+            },
+            colored: Balance {
+                settled: 0,
+                future: 0,
+                spendable: 0,
+            },
+        };
+        let delta = btc_balance_delta(&balance(1000, 900), &balance(400, 900));
+        assert_eq!(delta.vanilla.settled, -600);
+        assert_eq!(delta.vanilla.spendable, 0);
+        assert_eq!(delta.colored, BalanceDelta::default());
+    }
+}
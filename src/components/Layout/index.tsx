@@ -15,7 +15,7 @@ import React, { useEffect, useState, useRef } from 'react'
 import { NavLink, useLocation, useNavigate } from 'react-router-dom'
 import { toast, ToastContainer } from 'react-toastify'
 
-import { WALLET_SETUP_PATH } from '../../app/router/paths'
+import { WALLET_SETUP_PATH, WALLET_UNLOCK_PATH } from '../../app/router/paths'
 import { useAppDispatch, useAppSelector } from '../../app/store/hooks'
 import logo from '../../assets/logo.svg'
 import { useOnClickOutside } from '../../hooks/useOnClickOutside'
@@ -48,6 +48,99 @@ interface Props {
   children: React.ReactNode
 }
 
+interface NodeStartedEvent {
+  account_name: string
+  daemon_listening_port: string
+  ldk_peer_listening_port: string
+  auto_assigned: boolean
+}
+
+interface NodeUnlockedEvent {
+  account_name: string
+  already_unlocked: boolean
+}
+
+interface NodeBackupFinishedEvent {
+  account_name: string
+  reason: string
+  backup: { path: string } | null
+  error: string | null
+}
+
+interface NodeBackupUnlockFailedEvent {
+  account_name: string
+  message: string
+}
+
+interface PaymentReceivedEvent {
+  account_name: string
+  source: 'lightning' | 'onchain'
+  id: string
+  amount_msat: number | null
+  asset_id: string | null
+  asset_amount: number | null
+  confirmed: boolean
+}
+
+interface TransferSettledEvent {
+  account_name: string
+  asset_id: string
+  transfer: { idx: number; amount: number; kind: string }
+}
+
+interface ChannelStateChangedEvent {
+  account_name: string
+  channel_id: string
+  status: string
+}
+
+interface PeerConnectionEvent {
+  account_name: string
+  pubkey: string
+  alias: string | null
+  status: 'connected' | 'retrying' | 'failed'
+  attempt: number
+  error: string | null
+  next_retry_secs: number | null
+}
+
+interface UtxoMaintenanceEvent {
+  account_name: string
+  entry: {
+    free_before: number
+    target: number
+    error: string | null
+  }
+}
+
+interface LiquidityAlertEvent {
+  account_name: string
+  channel_id: string
+  kind: 'inbound' | 'outbound' | 'asset_inbound' | 'asset_outbound'
+  asset_id: string | null
+  available: number
+  threshold: number
+  low: boolean
+}
+
+interface LspOrderEvent {
+  account_name: string
+  order: {
+    order_id: string
+    lsp_balance_sat: number
+    order_total_sat: number
+    order_state: 'CREATED' | 'COMPLETED' | 'FAILED'
+    payment_state: string | null
+    error: string | null
+  }
+}
+
+interface NodeUnlockErrorEvent {
+  account_name: string
+  error: { kind: string }
+  message: string
+}
+
 // Define types for modal actions
 type ModalActionType = 'deposit' | 'withdraw' | 'none'
 
@@ -380,6 +473,7 @@ export const Layout = (props: Props) => {
   const [isLoggingOut, setIsLoggingOut] = useState(false)
 
   const { toggleNotificationPanel, notifications } = useNotification()
+  const nodeSettings = useAppSelector((state) => state.nodeSettings.data)
 
   useOnClickOutside(channelMenuRef, () => setIsChannelMenuOpen(false))
   useOnClickOutside(transactionMenuRef, () => setIsTransactionMenuOpen(false))
@@ -483,6 +577,176 @@ export const Layout = (props: Props) => {
     checkDeposits()
   }, [data, error, shouldPoll, lastDeposit, isFetching])
 
+  // Keep the node API base URL in sync with the ports the node actually
+  // started on (they differ from the configured ones in auto ports mode)
+  useEffect(() => {
+    const unlisten = listen<NodeStartedEvent>('node-started', (event) => {
+      const { account_name, daemon_listening_port, ldk_peer_listening_port } =
+        event.payload
+      const node_url = `http://localhost:${daemon_listening_port}`
+      if (
+        account_name !== nodeSettings.name ||
+        !nodeSettings.datapath ||
+        node_url === nodeSettings.node_url
+      ) {
+        return
+      }
+      dispatch(
+        nodeSettingsActions.setNodeSettings({
+          ...nodeSettings,
+          daemon_listening_port,
+          ldk_peer_listening_port,
+          node_url,
+        })
+      )
+    })
+
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [nodeSettings, dispatch])
+
+  // Report the outcome of the backend auto-unlock (`auto_unlock` setting)
+  useEffect(() => {
+    const unlistenUnlocked = listen<NodeUnlockedEvent>(
+      'node-unlocked',
+      (event) => {
+        if (!event.payload.already_unlocked) {
+          toast.success('Node unlocked')
+        }
+      }
+    )
+    const unlistenError = listen<NodeUnlockErrorEvent>(
+      'node-unlock-error',
+      (event) => {
+        toast.error(`Automatic unlock failed: ${event.payload.message}`)
+      }
+    )
+
+    return () => {
+      unlistenUnlocked.then((fn) => fn())
+      unlistenError.then((fn) => fn())
+    }
+  }, [])
+
+  // Scheduled node backups lock the node for a moment, so report their outcome
+  useEffect(() => {
+    const unlisten = listen<NodeBackupFinishedEvent>(
+      'node-backup-finished',
+      (event) => {
+        const { error, backup } = event.payload
+        if (error) {
+          toast.error(`Node backup failed: ${error}`)
+        } else if (backup) {
+          toast.info(`Node backed up to ${backup.path}`)
+        }
+      }
+    )
+    // The node stays locked until it is unlocked by hand
+    const unlistenUnlockFailed = listen<NodeBackupUnlockFailedEvent>(
+      'node-backup-unlock-failed',
+      (event) => {
+        toast.error(`${event.payload.message}. Click to unlock it.`, {
+          autoClose: false,
+          onClick: () => navigate(WALLET_UNLOCK_PATH),
+        })
+      }
+    )
+
+    return () => {
+      unlisten.then((fn) => fn())
+      unlistenUnlockFailed.then((fn) => fn())
+    }
+  }, [navigate])
+
+  // The backend watches the node for these and reports each one only once
+  useEffect(() => {
+    const unlisteners = [
+      listen<PaymentReceivedEvent>('payment-received', (event) => {
+        const { source, amount_msat, asset_id, asset_amount, confirmed } =
+          event.payload
+        const amount =
+          asset_id && asset_amount !== null
+            ? `${asset_amount} of ${asset_id}`
+            : `${Math.floor((amount_msat ?? 0) / 1000)} sats`
+        toast.success(
+          source === 'lightning'
+            ? `Lightning payment received: ${amount}`
+            : `On-chain payment received: ${amount}${confirmed ? '' : ' (unconfirmed)'}`
+        )
+      }),
+      listen<TransferSettledEvent>('transfer-settled', (event) => {
+        const { asset_id, transfer } = event.payload
+        toast.success(`RGB transfer of ${transfer.amount} ${asset_id} settled`)
+      }),
+      listen<ChannelStateChangedEvent>('channel-state-changed', (event) => {
+        const { channel_id, status } = event.payload
+        toast.info(`Channel ${channel_id.slice(0, 8)}… is now ${status}`)
+      }),
+      listen<UtxoMaintenanceEvent>('utxo-maintenance', (event) => {
+        const { free_before, target, error } = event.payload.entry
+        if (error) {
+          toast.warning(`Could not create colored UTXOs: ${error}`)
+        } else {
+          toast.info(
+            `Only ${free_before} free colored UTXOs were left, topped up to ${target}`
+          )
+        }
+      }),
+      listen<LiquidityAlertEvent>('liquidity-alert', (event) => {
+        const { channel_id, kind, asset_id, available, threshold, low } =
+          event.payload
+        const side = kind.endsWith('inbound') ? 'Inbound' : 'Outbound'
+        const unit = asset_id ?? 'sats'
+        const channel = `channel ${channel_id.slice(0, 8)}…`
+        if (low) {
+          toast.warning(
+            `${side} liquidity of ${channel} is low: ${available} ${unit} (minimum ${threshold})`
+          )
+        } else {
+          toast.info(`${side} liquidity of ${channel} recovered`)
+        }
+      }),
+      listen<LspOrderEvent>('lsp-order-changed', (event) => {
+        const {
+          lsp_balance_sat,
+          order_total_sat,
+          order_state,
+          payment_state,
+          error,
+        } = event.payload.order
+        if (error) {
+          toast.error(`LSP channel order: ${error}`)
+        } else if (order_state === 'COMPLETED') {
+          toast.success(
+            `The LSP opened a channel with ${lsp_balance_sat} sats of inbound liquidity`
+          )
+        } else if (order_state === 'FAILED') {
+          toast.error('The LSP channel order failed')
+        } else if (payment_state === 'HOLD' || payment_state === 'PAID') {
+          toast.info('LSP order paid, waiting for the channel to open')
+        } else {
+          toast.info(
+            `Ordered ${lsp_balance_sat} sats of inbound liquidity from the LSP for ${order_total_sat} sats`
+          )
+        }
+      }),
+      // Saved peers are reconnected quietly, only report when we give up
+      listen<PeerConnectionEvent>('peer-connection-changed', (event) => {
+        const { alias, pubkey, status, error } = event.payload
+        if (status === 'failed') {
+          toast.error(
+            `Could not reconnect to ${alias ?? pubkey.slice(0, 12)}: ${error}`
+          )
+        }
+      }),
+    ]
+
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()))
+    }
+  }, [])
+
   useEffect(() => {
     const handleBeforeUnload = () => {
       setIsShuttingDown(true)
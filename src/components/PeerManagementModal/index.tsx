@@ -1,8 +1,10 @@
+import { invoke } from '@tauri-apps/api/core'
 import { Users, Plus, Loader, X, Link as LinkIcon, Unlink } from 'lucide-react'
 import { useEffect, useState } from 'react'
 import { useForm } from 'react-hook-form'
 import { toast } from 'react-toastify'
 
+import { useAppSelector } from '../../app/store/hooks'
 import { nodeApi } from '../../slices/nodeApi/nodeApi.slice'
 
 interface PeerManagementModalProps {
@@ -11,11 +13,15 @@ interface PeerManagementModalProps {
 
 interface ConnectPeerForm {
   peerAddress: string
+  autoConnect: boolean
 }
 
 export const PeerManagementModal = ({ onClose }: PeerManagementModalProps) => {
   const [showConnectForm, setShowConnectForm] = useState(false)
-  const { register, handleSubmit, reset } = useForm<ConnectPeerForm>()
+  const { register, handleSubmit, reset } = useForm<ConnectPeerForm>({
+    defaultValues: { autoConnect: true },
+  })
+  const accountName = useAppSelector((state) => state.nodeSettings.data.name)
 
   const [getPeers, { data: peersData, isLoading }] =
     nodeApi.endpoints.listPeers.useLazyQuery()
@@ -34,6 +40,19 @@ export const PeerManagementModal = ({ onClose }: PeerManagementModalProps) => {
         peer_pubkey_and_addr: data.peerAddress,
       }).unwrap()
       toast.success('Connected to peer successfully')
+      const [pubkey, address] = data.peerAddress.trim().split('@')
+      // Saved with the flag either way, so unticking it stops the backend
+      // from reconnecting a peer remembered earlier
+      try {
+        await invoke('save_peer', {
+          accountName,
+          address,
+          autoConnect: data.autoConnect,
+          pubkey,
+        })
+      } catch (error) {
+        toast.warning(`Connected, but the peer was not saved: ${error}`)
+      }
       setShowConnectForm(false)
       reset()
       getPeers()
@@ -48,6 +67,12 @@ export const PeerManagementModal = ({ onClose }: PeerManagementModalProps) => {
         peer_pubkey: pubkey,
       }).unwrap()
       toast.success('Disconnected from peer')
+      // Forget the peer too, or it is reconnected on the next node start
+      try {
+        await invoke('delete_saved_peer', { accountName, pubkey })
+      } catch (error) {
+        toast.warning(`Disconnected, but the saved peer was kept: ${error}`)
+      }
       getPeers()
     } catch (error: any) {
       toast.error(error.data?.error || 'Failed to disconnect from peer')
@@ -106,6 +131,14 @@ export const PeerManagementModal = ({ onClose }: PeerManagementModalProps) => {
                 Cancel
               </button>
             </div>
+            <label className="mt-3 flex items-center gap-2 text-sm text-slate-400">
+              <input
+                {...register('autoConnect')}
+                className="accent-blue-600"
+                type="checkbox"
+              />
+              Reconnect to this peer when the node starts
+            </label>
           </form>
         ) : (
           <button
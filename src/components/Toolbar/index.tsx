@@ -10,6 +10,7 @@ import { useAppDispatch } from '../../app/store/hooks'
 import { MinidenticonImg } from '../../components/MinidenticonImg'
 import { Spinner } from '../../components/Spinner'
 import { BitcoinNetwork } from '../../constants'
+import { formatDate } from '../../helpers/date'
 import {
   nodeSettingsActions,
   setSettingsAsync,
@@ -28,6 +29,8 @@ export interface Account {
   rpc_connection_url: string
   daemon_listening_port: string
   ldk_peer_listening_port: string
+  last_used?: number | null
+  preset_id?: number | null
 }
 
 interface ModalProps {
@@ -162,6 +165,11 @@ const NodeCard: React.FC<NodeCardProps> = ({
                 {nodeType}
               </span>
             </div>
+            {account.last_used && (
+              <div className="text-xs text-gray-500 mt-1 truncate">
+                Last used {formatDate(account.last_used * 1000)}
+              </div>
+            )}
           </div>
         )}
 
@@ -248,6 +256,8 @@ const useAccounts = () => {
     const fetchAccounts = async () => {
       try {
         const fetchedAccounts = (await invoke('get_accounts')) as Account[]
+        // Most recently used accounts first
+        fetchedAccounts.sort((a, b) => (b.last_used ?? 0) - (a.last_used ?? 0))
         setAccounts(fetchedAccounts)
       } catch (err) {
         setError(
@@ -445,6 +455,8 @@ export const Toolbar: React.FC<ToolbarProps> = ({ isCollapsed = false }) => {
         })
 
         try {
+          // Ports picked in auto ports mode are applied by the Layout's
+          // `node-started` listener
           await invoke('start_node', {
             accountName: node.name,
             daemonListeningPort: node.daemon_listening_port,
@@ -741,16 +753,15 @@ const DeleteNodeModalContent: React.FC<DeleteNodeModalContentProps> = ({
         <div className="bg-yellow-500/10 border border-yellow-500/20 rounded-lg p-4 text-left">
           <p className="text-yellow-500 font-medium mb-2">⚠️ Warning</p>
           <ul className="text-yellow-100/80 space-y-2 text-sm">
-            <li>• This action cannot be undone</li>
             {account.datapath ? (
               <>
+                <li>• The account will be removed from the app</li>
                 <li>
-                  • If you haven't backed up your node, you will permanently
-                  lose access to your funds
+                  • Local node data will be moved to an archive and can be
+                  recovered until the archive is purged
                 </li>
-                <li>• All local node data will be permanently deleted</li>
                 <li className="break-all">
-                  • Data path to be deleted: {account.datapath}
+                  • Data path to be archived: {account.datapath}
                 </li>
               </>
             ) : (
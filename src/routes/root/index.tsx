@@ -8,11 +8,24 @@ import {
   WALLET_SETUP_PATH,
   WALLET_UNLOCK_PATH,
 } from '../../app/router/paths'
+import { useAppDispatch } from '../../app/store/hooks'
 import { Layout } from '../../components/Layout'
 import { nodeApi } from '../../slices/nodeApi/nodeApi.slice'
+import {
+  NodeSettings,
+  setSettingsAsync,
+} from '../../slices/nodeSettings/nodeSettings.slice'
+
+interface NodePorts {
+  daemon_listening_port: string
+  ldk_peer_listening_port: string
+}
+
+type Account = Omit<NodeSettings, 'maker_urls'> & { maker_urls: string }
 
 export const RootRoute = () => {
   const navigate = useNavigate()
+  const dispatch = useAppDispatch()
   const [nodeInfo, nodeInfoResponse] = nodeApi.endpoints.nodeInfo.useLazyQuery()
 
   useEffect(() => {
@@ -32,6 +45,26 @@ export const RootRoute = () => {
 
   useEffect(() => {
     async function run() {
+      // Restore the current account, which persists across restarts
+      const account = await invoke<Account | null>('get_current_account')
+      if (account) {
+        const ports = await invoke<NodePorts | null>('get_running_node_ports')
+        await dispatch(
+          setSettingsAsync({
+            ...account,
+            maker_urls: account.maker_urls
+              .split(',')
+              .filter((url) => url.trim() !== ''),
+            ...(ports && account.datapath
+              ? {
+                  ...ports,
+                  node_url: `http://localhost:${ports.daemon_listening_port}`,
+                }
+              : {}),
+          })
+        )
+      }
+
       const nodeInfoResponse = await nodeInfo()
       const error: any = nodeInfoResponse.error
 
@@ -46,7 +79,7 @@ export const RootRoute = () => {
       }
     }
     run()
-  }, [navigate, nodeInfo])
+  }, [dispatch, navigate, nodeInfo])
 
   return (
     <Layout>
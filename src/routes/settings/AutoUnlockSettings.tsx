@@ -0,0 +1,177 @@
+import { invoke } from '@tauri-apps/api/core'
+import { Key } from 'lucide-react'
+import { useCallback, useEffect, useState } from 'react'
+import { toast } from 'react-toastify'
+
+interface SecretStoreStatus {
+  initialized: boolean
+  unlocked: boolean
+}
+
+interface AutoUnlockSettingsProps {
+  accountName: string
+}
+
+const inputClassName =
+  'flex-1 px-4 py-2.5 bg-gray-700/50 border border-gray-600 rounded-xl text-white placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent'
+const buttonClassName =
+  'px-4 py-2.5 bg-[#4361EE] text-white rounded-xl hover:bg-[#3651DE] disabled:opacity-50 disabled:cursor-not-allowed transition-colors'
+
+// Opt-in for the backend auto-unlock: the node password is kept in the
+// secret store, which has to be unlocked with the master password first.
+export const AutoUnlockSettings = ({
+  accountName,
+}: AutoUnlockSettingsProps) => {
+  const [status, setStatus] = useState<SecretStoreStatus | null>(null)
+  const [autoUnlock, setAutoUnlock] = useState(false)
+  const [hasNodePassword, setHasNodePassword] = useState(false)
+  const [masterPassword, setMasterPassword] = useState('')
+  const [nodePassword, setNodePassword] = useState('')
+  const [isBusy, setIsBusy] = useState(false)
+
+  const refresh = useCallback(async () => {
+    try {
+      const [storeStatus, enabled, secrets] = await Promise.all([
+        invoke<SecretStoreStatus>('get_secret_store_status'),
+        invoke<boolean>('get_account_setting', {
+          accountName,
+          key: 'auto_unlock',
+        }),
+        invoke<string[]>('list_account_secrets', { accountName }),
+      ])
+      setStatus(storeStatus)
+      setAutoUnlock(enabled)
+      setHasNodePassword(secrets.includes('node_password'))
+    } catch (error) {
+      console.error('Failed to load the auto-unlock settings:', error)
+    }
+  }, [accountName])
+
+  useEffect(() => {
+    refresh()
+  }, [refresh])
+
+  const run = async (action: () => Promise<unknown>, success: string) => {
+    setIsBusy(true)
+    try {
+      await action()
+      toast.success(success)
+      await refresh()
+    } catch (error) {
+      toast.error(String(error))
+    } finally {
+      setIsBusy(false)
+    }
+  }
+
+  const handleMasterPassword = () =>
+    run(async () => {
+      await invoke(
+        status?.initialized ? 'unlock_secret_store' : 'setup_secret_store',
+        { password: masterPassword }
+      )
+      setMasterPassword('')
+    }, 'Secret store unlocked')
+
+  const handleSaveNodePassword = () =>
+    run(async () => {
+      await invoke('set_account_secret', {
+        accountName,
+        key: 'node_password',
+        value: nodePassword,
+      })
+      setNodePassword('')
+    }, 'Node password saved')
+
+  const handleToggle = (enabled: boolean) =>
+    run(
+      () =>
+        invoke('set_account_setting', {
+          accountName,
+          key: 'auto_unlock',
+          value: enabled,
+        }),
+      enabled ? 'Automatic unlock enabled' : 'Automatic unlock disabled'
+    )
+
+  if (!status) {
+    return null
+  }
+
+  return (
+    <div className="bg-gray-800/80 backdrop-blur-sm p-8 rounded-2xl shadow-2xl border border-gray-700">
+      <div className="flex items-center gap-2 mb-6">
+        <Key className="w-5 h-5 text-blue-400" />
+        <h3 className="text-xl font-semibold text-white">Automatic Unlock</h3>
+      </div>
+
+      <div className="space-y-4">
+        {!status.unlocked ? (
+          <div className="space-y-2">
+            <p className="text-sm text-gray-400">
+              {status.initialized
+                ? 'Unlock the secret store with your master password.'
+                : 'Choose a master password to encrypt the saved node password.'}
+            </p>
+            <div className="flex gap-2">
+              <input
+                className={inputClassName}
+                onChange={(e) => setMasterPassword(e.target.value)}
+                placeholder="Master password"
+                type="password"
+                value={masterPassword}
+              />
+              <button
+                className={buttonClassName}
+                disabled={isBusy || !masterPassword}
+                onClick={handleMasterPassword}
+              >
+                {status.initialized ? 'Unlock' : 'Set up'}
+              </button>
+            </div>
+          </div>
+        ) : (
+          <div className="space-y-2">
+            <p className="text-sm text-gray-400">
+              {hasNodePassword
+                ? 'A node password is saved. Enter it again to replace it.'
+                : 'Save the node password used to unlock this wallet.'}
+            </p>
+            <div className="flex gap-2">
+              <input
+                className={inputClassName}
+                onChange={(e) => setNodePassword(e.target.value)}
+                placeholder="Node password"
+                type="password"
+                value={nodePassword}
+              />
+              <button
+                className={buttonClassName}
+                disabled={isBusy || !nodePassword}
+                onClick={handleSaveNodePassword}
+              >
+                Save
+              </button>
+            </div>
+          </div>
+        )}
+
+        <label className="flex items-center gap-2 text-sm text-gray-300">
+          <input
+            checked={autoUnlock}
+            className="accent-blue-600"
+            disabled={isBusy || (!autoUnlock && !hasNodePassword)}
+            onChange={(e) => handleToggle(e.target.checked)}
+            type="checkbox"
+          />
+          Unlock the node automatically after it starts
+        </label>
+        {autoUnlock && !status.unlocked && (
+          <p className="text-xs text-yellow-400">
+            The secret store must be unlocked before the node starts.
+          </p>
+        )}
+      </div>
+    </div>
+  )
+}
@@ -42,6 +42,7 @@ import {
   setNodeConnectionString,
 } from '../../slices/settings/settings.slice'
 
+import { AutoUnlockSettings } from './AutoUnlockSettings'
 import { TerminalLogDisplay } from './TerminalLogDisplay'
 
 interface FormFields {
@@ -873,6 +874,10 @@ export const Component: React.FC = () => {
                 </div>
               </div>
             </div>
+
+            {isLocalNode && (
+              <AutoUnlockSettings accountName={currentAccount.name} />
+            )}
           </div>
         </div>
 
@@ -943,6 +943,7 @@ export const Component = () => {
       createSwapExecutor(
         assets,
         pubKey,
+        makerConnectionUrl,
         selectedPairFeed,
         selectedPair,
         parseAssetAmount,
@@ -959,6 +960,7 @@ export const Component = () => {
     [
       assets,
       pubKey,
+      makerConnectionUrl,
       selectedPairFeed,
       selectedPair,
       parseAssetAmount,
@@ -1,4 +1,5 @@
 import { FetchBaseQueryError } from '@reduxjs/toolkit/query'
+import { invoke } from '@tauri-apps/api/core'
 import { toast } from 'react-toastify'
 
 import { TradingPair } from '../../../slices/makerApi/makerApi.slice'
@@ -89,6 +90,7 @@ export const validateSwapString = (
 export const createSwapExecutor = (
   assets: NiaAsset[],
   pubKey: string,
+  makerUrl: string,
   selectedPairFeed: any,
   selectedPair: TradingPair | null,
   parseAssetAmount: (
@@ -243,6 +245,11 @@ export const createSwapExecutor = (
       }
 
       logger.info('Swap executed successfully!')
+      // The node doesn't know the maker, keep it for history exports
+      invoke('record_swap_maker', {
+        makerUrl,
+        paymentHash: payment_hash,
+      }).catch((error) => logger.warn('Failed to record the swap maker', error))
       toast.update(toastId, {
         autoClose: 5000,
         closeOnClick: true,
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { ChevronDown, ArrowLeft, Eye, EyeOff, Lock } from 'lucide-react'
 import { useState, useEffect } from 'react'
 import { SubmitHandler, useForm } from 'react-hook-form'
@@ -20,6 +21,10 @@ interface Fields {
   password: string
 }
 
+interface AutoUnlockEvent {
+  account_name: string
+}
+
 export const Component = () => {
   const nodeSettings = useAppSelector((state) => state.nodeSettings.data)
   const [unlock] = nodeApi.endpoints.unlock.useLazyQuery()
@@ -33,6 +38,7 @@ export const Component = () => {
   const [errors, setErrors] = useState<string[]>([])
   const [unlockError, setUnlockError] = useState<string | null>(null)
   const [isConnectionDetailsOpen, setIsConnectionDetailsOpen] = useState(false)
+  const [isAutoUnlocking, setIsAutoUnlocking] = useState(false)
 
   // Check if the node is already unlocked when the component mounts
   useEffect(() => {
@@ -45,6 +51,39 @@ export const Component = () => {
     checkNodeStatus()
   }, [])
 
+  // Wait for the backend auto-unlock instead of racing it with our own unlock
+  useEffect(() => {
+    const accountName = nodeSettings.name
+    const unlistenUnlocked = listen<AutoUnlockEvent>(
+      'node-unlocked',
+      (event) => {
+        if (event.payload.account_name === accountName) {
+          navigate(WALLET_DASHBOARD_PATH)
+        }
+      }
+    )
+    const unlistenError = listen<AutoUnlockEvent>(
+      'node-unlock-error',
+      (event) => {
+        if (event.payload.account_name === accountName) {
+          setIsAutoUnlocking(false)
+        }
+      }
+    )
+    // Subscribe first so an outcome arriving meanwhile is not missed
+    Promise.all([unlistenUnlocked, unlistenError])
+      .then(() => invoke<boolean>('is_auto_unlock_pending', { accountName }))
+      .then(setIsAutoUnlocking)
+      .catch((error) =>
+        console.error('Failed to check the automatic unlock:', error)
+      )
+
+    return () => {
+      unlistenUnlocked.then((fn) => fn())
+      unlistenError.then((fn) => fn())
+    }
+  }, [nodeSettings.name])
+
   const unlockForm = useForm<Fields>({
     defaultValues: {
       password: '',
@@ -388,21 +427,25 @@ export const Component = () => {
 
   return (
     <Layout className="min-h-screen bg-gradient-to-b from-gray-900 to-black">
-      {isUnlocking ? (
+      {isUnlocking || isAutoUnlocking ? (
         <SetupLayout
           centered={true}
           fullHeight
           icon={<Lock />}
           maxWidth="xl"
-          subtitle="Please wait while we access your wallet"
+          subtitle={
+            isAutoUnlocking
+              ? 'Unlocking with the saved node password'
+              : 'Please wait while we access your wallet'
+          }
           title="Unlocking Wallet"
         >
           <Card className="w-full max-w-3xl mx-auto p-6 bg-gray-900 border border-gray-800 rounded-xl">
             <UnlockingProgress
               errorMessage={unlockError || undefined}
-              isUnlocking={isUnlocking}
+              isUnlocking
               onBack={handleBack}
-              onCancel={handleCancelUnlocking}
+              onCancel={isAutoUnlocking ? undefined : handleCancelUnlocking}
             />
           </Card>
         </SetupLayout>